@@ -0,0 +1,74 @@
+/// Bearer token authentication for json-rpc endpoints
+use hyper::header::AUTHORIZATION;
+use hyper::HeaderMap;
+
+/// The access level of a caller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    /// Methods without side effects.
+    Read,
+    /// All methods.
+    Full,
+}
+
+/// Authentication is disabled if neither token is set.
+#[derive(Clone, Debug, Default)]
+pub struct AuthConfig {
+    /// Grants `Scope::Full`.
+    pub token: Option<String>,
+    /// Grants `Scope::Read`.
+    pub read_token: Option<String>,
+}
+
+impl AuthConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.token.is_some() || self.read_token.is_some()
+    }
+
+    /// Returns the scope for the `Authorization: Bearer <token>` header
+    /// in `headers` or `None` if the caller is not authenticated.
+    /// Every caller has `Scope::Full` if authentication is disabled.
+    pub fn scope(&self, headers: &HeaderMap) -> Option<Scope> {
+        if !self.is_enabled() {
+            return Some(Scope::Full);
+        }
+
+        let token = headers
+            .get(AUTHORIZATION)
+            .and_then(|e| e.to_str().ok())
+            .and_then(|e| e.strip_prefix("Bearer "))?
+            .trim()
+            .as_bytes();
+        let matches = |expected: &Option<String>| match expected {
+            Some(expected) => constant_time_eq(expected.as_bytes(), token),
+            None => false,
+        };
+
+        if matches(&self.token) {
+            return Some(Scope::Full);
+        }
+        if matches(&self.read_token) {
+            return Some(Scope::Read);
+        }
+
+        None
+    }
+
+    /// Returns an error if `headers` don't grant at least `required`.
+    pub fn check(&self, headers: &HeaderMap, required: Scope) -> Result<(), String> {
+        match self.scope(headers) {
+            None => Err("unauthorized".to_string()),
+            Some(scope) if scope < required => Err("insufficient scope".to_string()),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+/// Compares `a` and `b` in constant time, only leaks the length.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,27 @@
+use crate::prover::CircuitConfig;
+
+/// The circuit parameters of the prover, ordered by `block_gas_limit`.
+pub const CIRCUIT_CONFIGS: [CircuitConfig; 2] = [
+    CircuitConfig {
+        block_gas_limit: 63000,
+        max_txs: 3,
+        max_calldata: 10500,
+        max_bytecode: 24634,
+        max_rws: 476052,
+        min_k: 20,
+        pad_to: 476052,
+        min_k_aggregation: 26,
+        keccak_padding: 336000,
+    },
+    CircuitConfig {
+        block_gas_limit: 300000,
+        max_txs: 14,
+        max_calldata: 69750,
+        max_bytecode: 139500,
+        max_rws: 3161966,
+        min_k: 23,
+        pad_to: 3161966,
+        min_k_aggregation: 26,
+        keccak_padding: 1600000,
+    },
+];
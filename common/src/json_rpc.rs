@@ -1,6 +1,7 @@
 /// Common utilities for json-rpc
 use hyper::body::Buf;
 use hyper::client::HttpConnector;
+use hyper::header::HeaderValue;
 use hyper::Body;
 use hyper::Request;
 use hyper::Uri;
@@ -15,6 +16,42 @@ pub struct JsonRpcError {
     pub message: String,
 }
 
+impl JsonRpcError {
+    /// Returns true if this is the error of a reverted `eth_call` or `eth_estimateGas`.
+    /// Nodes either use the code `3` or the generic server error `-32000`
+    /// with the message `execution reverted`.
+    pub fn is_revert(&self) -> bool {
+        self.code == 3 || (self.code == -32000 && self.message.starts_with("execution reverted"))
+    }
+}
+
+/// Errors of `jsonrpc_call`.
+#[derive(Debug)]
+pub enum JsonRpcRequestError {
+    /// The request failed or timed out, or the response is malformed.
+    Transport(String),
+    /// The node responded with an error.
+    Response(JsonRpcError),
+    /// The node responded without result and error.
+    NoResult,
+}
+
+impl std::fmt::Display for JsonRpcRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transport(err) => write!(f, "{}", err),
+            Self::Response(err) => write!(f, "{}", err.message),
+            Self::NoResult => write!(f, "no result in response"),
+        }
+    }
+}
+
+impl From<JsonRpcRequestError> for String {
+    fn from(err: JsonRpcRequestError) -> Self {
+        err.to_string()
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct JsonRpcResponseError {
     pub jsonrpc: String,
@@ -46,13 +83,46 @@ pub async fn jsonrpc_request_client<T: Serialize + Send + Sync, R: DeserializeOw
     method: &str,
     params: T,
 ) -> Result<R, String> {
+    jsonrpc_call(timeout, client, uri, method, params)
+        .await
+        .map_err(String::from)
+}
+
+/// Same as `jsonrpc_request_client` but keeps the error response of the node,
+/// e.g. to tell reverts apart from other errors.
+pub async fn jsonrpc_call<T: Serialize + Send + Sync, R: DeserializeOwned>(
+    timeout: u64,
+    client: &hyper::Client<HttpConnector>,
+    uri: &Uri,
+    method: &str,
+    params: T,
+) -> Result<R, JsonRpcRequestError> {
+    jsonrpc_call_with_token(timeout, client, uri, None, method, params).await
+}
+
+/// Same as `jsonrpc_call` but sends `token` as `Authorization: Bearer <token>` header.
+pub async fn jsonrpc_call_with_token<T: Serialize + Send + Sync, R: DeserializeOwned>(
+    timeout: u64,
+    client: &hyper::Client<HttpConnector>,
+    uri: &Uri,
+    token: Option<&str>,
+    method: &str,
+    params: T,
+) -> Result<R, JsonRpcRequestError> {
     #[derive(Debug, Deserialize)]
     struct JsonRpcResponseInternal<T> {
         result: Option<T>,
         error: Option<JsonRpcError>,
     }
 
-    let node_req = Request::post(uri);
+    let mut node_req = Request::post(uri);
+    if let Some(token) = token {
+        // the token is not part of the error
+        let value = HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|_| {
+            JsonRpcRequestError::Transport("invalid characters in token".to_string())
+        })?;
+        node_req = node_req.header(hyper::header::AUTHORIZATION, value);
+    }
     let req_obj = JsonRpcRequest {
         jsonrpc: "2.0".to_string(),
         id: 0.into(),
@@ -68,26 +138,28 @@ pub async fn jsonrpc_request_client<T: Serialize + Send + Sync, R: DeserializeOw
     log::trace!("jsonrpc_request_client: {} {}", uri, method);
 
     let json = tokio::time::timeout(std::time::Duration::from_millis(timeout), async {
-        let err_str = &uri.to_string();
-        let resp = client.request(node_req).await.expect(err_str);
-        let body = hyper::body::aggregate(resp).await.expect(err_str);
+        let resp = client
+            .request(node_req)
+            .await
+            .map_err(|e| format!("{}: {}", uri, e))?;
+        let body = hyper::body::aggregate(resp)
+            .await
+            .map_err(|e| format!("{}: {}", uri, e))?;
         let json: JsonRpcResponseInternal<R> =
-            serde_json::from_reader(body.reader()).expect(err_str);
+            serde_json::from_reader(body.reader()).map_err(|e| format!("{}: {}", uri, e))?;
 
-        json
+        Ok::<_, String>(json)
     })
     .await
-    .map_err(|e| e.to_string())?;
-
-    if json.error.is_some() {
-        return Err(json.error.unwrap().message);
-    }
+    .map_err(|e| e.to_string())
+    .and_then(|res| res)
+    .map_err(JsonRpcRequestError::Transport)?;
 
-    if json.result.is_none() {
-        return Err("no result in response".to_string());
+    if let Some(err) = json.error {
+        return Err(JsonRpcRequestError::Response(err));
     }
 
-    Ok(json.result.unwrap())
+    json.result.ok_or(JsonRpcRequestError::NoResult)
 }
 
 pub async fn jsonrpc_request<T: Serialize + Send + Sync, R: DeserializeOwned>(
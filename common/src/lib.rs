@@ -1,2 +1,5 @@
+pub mod auth;
+pub mod circuit_autogen;
 pub mod json_rpc;
+pub mod metrics;
 pub mod prover;
@@ -0,0 +1,30 @@
+/// Common utilities for metrics of the `prometheus` crate
+use prometheus::core::Collector;
+use prometheus::Encoder;
+use prometheus::Registry;
+use prometheus::TextEncoder;
+
+/// Buckets for durations in seconds, from 10ms to ~2.8 hours.
+pub const DURATION_BUCKETS: [f64; 15] = [
+    0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1200.0, 3600.0, 10000.0,
+];
+
+/// Registers `metric` in `registry` and returns it.
+/// Panics if the metric is invalid or its name is already registered.
+pub fn register<M: Collector + Clone + 'static>(registry: &Registry, metric: M) -> M {
+    registry
+        .register(Box::new(metric.clone()))
+        .expect("register metric");
+
+    metric
+}
+
+/// Renders all metrics of `registry` in the prometheus text format.
+pub fn render(registry: &Registry) -> String {
+    let mut out = Vec::new();
+    TextEncoder::new()
+        .encode(&registry.gather(), &mut out)
+        .expect("encode metrics");
+
+    String::from_utf8(out).expect("utf8")
+}
@@ -29,11 +29,19 @@ impl std::fmt::Debug for ProofResult {
     }
 }
 
+/// The maximum number of blocks of one aggregated proof, see `ProofRequestOptions::to_block`.
+/// The autogen verifiers cover each number of blocks up to this.
+pub const MAX_AGGREGATION_BLOCKS: usize = 4;
+
+/// For requests of multiple blocks, see `ProofRequestOptions::to_block`:
+/// - `config` and `circuit` are for the last block
+/// - `aggregation` aggregates the circuit proofs of all blocks in order
+/// - `gas` is the maximum over all blocks
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct Proofs {
     /// Circuit configuration used
     pub config: CircuitConfig,
-    // Proof result for circuit
+    // Proof result for circuit, only set if the proof covers a single block
     pub circuit: ProofResult,
     /// Aggregation proof for circuit, if requested
     pub aggregation: ProofResult,
@@ -48,6 +56,12 @@ pub struct ProofRequestOptions {
     pub circuit: String,
     /// the block number
     pub block: u64,
+    /// The last block number if the proof covers the blocks `block..=to_block`.
+    /// The circuit proofs of these blocks are aggregated into one proof,
+    /// this requires `aggregate` unless `mock` is set.
+    /// Covers at most `MAX_AGGREGATION_BLOCKS` blocks.
+    #[serde(default)]
+    pub to_block: Option<u64>,
     /// the rpc url
     pub rpc: String,
     /// retry proof computation if error
@@ -67,11 +81,16 @@ pub struct ProofRequestOptions {
     /// Verifies the proof after computation.
     #[serde(default = "default_bool")]
     pub verify_proof: bool,
+    /// Tasks with a higher priority are computed first, defaults to 0.
+    /// Not part of the task identity.
+    #[serde(default)]
+    pub priority: Option<i32>,
 }
 
 impl PartialEq for ProofRequestOptions {
     fn eq(&self, other: &Self) -> bool {
         self.block == other.block
+            && self.to_block == other.to_block
             && self.rpc == other.rpc
             && self.param == other.param
             && self.circuit == other.circuit
@@ -80,12 +99,24 @@ impl PartialEq for ProofRequestOptions {
     }
 }
 
+/// A time limited claim of a node to compute a task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskLease {
+    /// The node that claimed the task.
+    pub node_id: String,
+    /// Unix timestamp in milliseconds, renewed while the node works on the task.
+    pub expires: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofRequest {
     pub options: ProofRequestOptions,
     pub result: Option<Result<Proofs, String>>,
     /// A counter to keep track of changes of the `result` field
     pub edition: u64,
+    /// The current claim for this task - if any.
+    #[serde(default)]
+    pub lease: Option<TaskLease>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -95,14 +126,20 @@ pub struct NodeInformation {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct NodeStatus {
-    pub id: String,
-    /// The current active task this instance wants to obtain or is working on.
+pub struct SlotStatus {
+    /// The task this slot wants to obtain or is working on.
     pub task: Option<ProofRequestOptions>,
-    /// `true` if this instance started working on `task`
+    /// `true` if this slot started working on `task`
     pub obtained: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeStatus {
+    pub id: String,
+    /// The status of each proving slot of this instance.
+    pub slots: Vec<SlotStatus>,
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct CircuitConfig {
     pub block_gas_limit: usize,
@@ -28,9 +28,22 @@ contract ZkEvmL1Bridge is
   bytes32 public stateRoot;
   mapping (bytes32 => bytes32) commitments;
   mapping (bytes32 => bytes32) stateRoots;
+  mapping (bytes32 => bytes32) parentBlockHashes;
 
   function submitBlock (bytes calldata witness) external {
     _onlyEOA();
+    _submitBlock(witness);
+  }
+
+  /// @notice Submits multiple blocks in order, emits `BlockSubmitted` for each block.
+  function submitBlocks (bytes[] calldata witnesses) external {
+    _onlyEOA();
+    for (uint256 i = 0; i < witnesses.length; i++) {
+      _submitBlock(witnesses[i]);
+    }
+  }
+
+  function _submitBlock (bytes calldata witness) internal {
     emit BlockSubmitted();
 
     (
@@ -53,9 +66,60 @@ contract ZkEvmL1Bridge is
     }
     commitments[bytes32(blockHash)] = hash;
     stateRoots[blockHash] = blockStateRoot;
+    parentBlockHashes[blockHash] = parentBlockHash;
   }
 
   function finalizeBlock (bytes calldata proof) external {
+    _finalizeBlock(proof);
+  }
+
+  /// @notice Finalizes `blockHashes` in order with one proof for all blocks.
+  /// `proof` has the same layout as for `finalizeBlock` with the last block hash
+  /// and must be an aggregated proof that exposes the instances of every block
+  /// after the accumulator.
+  function finalizeBlocks (bytes32[] calldata blockHashes, bytes calldata proof) external {
+    require(blockHashes.length > 0);
+
+    uint256 last = blockHashes.length - 1;
+    // `blockHash, address, is_aggregated`, the accumulator limbs (16 * 32)
+    // and the instances of each block (5 * 32)
+    uint256 instanceOffset = 96 + 512;
+    require(proof.length > instanceOffset + blockHashes.length * 160);
+
+    bytes32 lastBlockHash;
+    uint256 isAggregated;
+    assembly {
+      lastBlockHash := calldataload(proof.offset)
+      isAggregated := calldataload(add(proof.offset, 64))
+    }
+    require(lastBlockHash == blockHashes[last]);
+    require(isAggregated != 0);
+
+    for (uint256 i = 0; i <= last; i++) {
+      bytes32 blockHash = blockHashes[i];
+      bytes32 expectedCommitmentHash = commitments[blockHash];
+      require(expectedCommitmentHash != 0, 'FBCOMMIT');
+      if (i != 0) {
+        require(parentBlockHashes[blockHash] == blockHashes[i - 1], 'FBPARENT');
+      }
+
+      bytes32 hash;
+      assembly {
+        let len := 160
+        let ptr := mload(64)
+        calldatacopy(ptr, add(proof.offset, add(instanceOffset, mul(i, len))), len)
+        hash := keccak256(ptr, len)
+      }
+      require(hash == expectedCommitmentHash, 'FBINST');
+
+      if (i != last) {
+        emit BlockFinalized(blockHash);
+      }
+    }
+    _finalizeBlock(proof);
+  }
+
+  function _finalizeBlock (bytes calldata proof) internal {
     require(proof.length > 32);
 
     bytes32 blockHash;
@@ -1,10 +1,12 @@
 use clap::Parser;
 use coordinator::config::Config;
+use coordinator::error::CoordinatorError;
 use coordinator::faucet::Faucet;
 use coordinator::shared_state::SharedState;
+use coordinator::structs::MessageDirection;
 use coordinator::utils::*;
 use env_logger::Env;
-use ethers_core::types::{Address, U64};
+use ethers_core::types::{Address, H256, U64};
 use hyper::body::Buf;
 use hyper::body::HttpBody;
 use hyper::client::HttpConnector;
@@ -12,16 +14,33 @@ use hyper::header::HeaderValue;
 use hyper::service::{make_service_fn, service_fn};
 use hyper::HeaderMap;
 use hyper::{Body, Method, Request, Response, Server, StatusCode, Uri};
+use std::future::Future;
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::time::Duration;
+use tokio::sync::watch;
 use tokio::task::spawn;
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
+use zkevm_common::auth::Scope;
 use zkevm_common::json_rpc::JsonRpcError;
 use zkevm_common::json_rpc::JsonRpcRequest;
 use zkevm_common::json_rpc::JsonRpcResponse;
 use zkevm_common::json_rpc::JsonRpcResponseError;
 
-const EVENT_LOOP_COOLDOWN: Duration = Duration::from_millis(3000);
+/// The delay between two runs of the `sync` stage.
+const SYNC_INTERVAL: Duration = Duration::from_millis(2000);
+/// The delay between two runs of the `mine` stage.
+/// Whether a block is sealed is up to the block policy of the `Config`.
+const MINE_INTERVAL: Duration = Duration::from_millis(1000);
+/// The delay between two runs of the `submit` and `relay` stages,
+/// these are also woken by the stages they depend on.
+const L1_STAGE_INTERVAL: Duration = Duration::from_millis(5000);
+/// The delay between two runs of the `faucet` stage.
+const FAUCET_INTERVAL: Duration = Duration::from_millis(3000);
+/// The number of attempts for each step of a stage.
+const STEP_MAX_ATTEMPTS: u32 = 5;
+/// The initial delay between attempts, doubles after each attempt.
+const STEP_BACKOFF: Duration = Duration::from_millis(500);
 /// allowed jsonrpc methods
 const PROXY_ALLOWED_METHODS: [&str; 40] = [
     "eth_chainId",
@@ -77,7 +96,9 @@ fn set_headers(headers: &mut HeaderMap, extended: bool) {
         );
         headers.insert(
             "access-control-allow-headers",
-            HeaderValue::from_static("origin, content-type, accept, x-requested-with"),
+            HeaderValue::from_static(
+                "origin, content-type, accept, x-requested-with, authorization",
+            ),
         );
         headers.insert("access-control-max-age", HeaderValue::from_static("300"));
     }
@@ -122,6 +143,16 @@ async fn handle_request(
             Ok(resp)
         }
 
+        // prometheus metrics
+        (&Method::GET, "/metrics") => {
+            let mut resp = Response::new(Body::from(shared_state.ro.metrics.render()));
+            resp.headers_mut().insert(
+                "content-type",
+                HeaderValue::from_static("text/plain; version=0.0.4"),
+            );
+            Ok(resp)
+        }
+
         // geth upstream json-rpc
         (&Method::POST, "/") => {
             let body_bytes = hyper::body::to_bytes(req.into_body()).await.unwrap();
@@ -178,7 +209,7 @@ async fn handle_request(
 
         // returns 503 if faucet is disabled else 200 and enqueues a faucet requests
         // that is processed asyncly.
-        // The faucet transfer can still fail if the `l1_wallet` has not enough ETH.
+        // The faucet transfer can still fail if the faucet wallet has not enough ETH.
         (&Method::GET, "/faucet") => {
             let receiver = req
                 .uri()
@@ -193,7 +224,9 @@ async fn handle_request(
                     *resp.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
                 }
                 Some(faucet) => {
-                    faucet.queue.lock().await.push_back(receiver);
+                    let mut queue = faucet.queue.lock().await;
+                    queue.push_back(receiver);
+                    shared_state.ro.metrics.faucet_queue.set(queue.len() as f64);
                     *resp.status_mut() = StatusCode::OK;
                 }
             }
@@ -203,8 +236,10 @@ async fn handle_request(
         }
 
         // coordinator rpc
-        // TODO: protect this interface from public consumption
+        // requires authentication if `Config::rpc_auth_token` or `rpc_auth_read_token` is set
         (&Method::POST, "/rpc") => {
+            let auth = shared_state.config.lock().await.rpc_auth();
+            let headers = req.headers().clone();
             let body_bytes = hyper::body::aggregate(req.into_body())
                 .await
                 .unwrap()
@@ -229,6 +264,23 @@ async fn handle_request(
             }
 
             let json_req = json_req.unwrap();
+            if let Err(err) = auth.check(&headers, method_scope(json_req.method.as_str())) {
+                let payload = serde_json::to_vec(&JsonRpcResponseError {
+                    jsonrpc: "2.0".to_string(),
+                    id: json_req.id,
+                    error: JsonRpcError {
+                        // server error
+                        code: -32001,
+                        message: err,
+                    },
+                })
+                .unwrap();
+                let mut resp = Response::new(Body::from(payload));
+                *resp.status_mut() = StatusCode::UNAUTHORIZED;
+                set_headers(resp.headers_mut(), false);
+                return Ok(resp);
+            }
+
             let result: Result<serde_json::Value, String> =
                 handle_method(json_req.method.as_str(), &json_req.params, &shared_state).await;
             let payload = match result {
@@ -279,7 +331,13 @@ async fn check_nodes(ctx: SharedState, client: hyper::Client<HttpConnector>) {
     addrs.sort_unstable();
     for addr in addrs {
         let uri = Uri::try_from(format!("http://{}", addr)).unwrap();
-        let header = get_chain_head(&client, &uri).await;
+        let header = match get_chain_head(&client, &uri).await {
+            Ok(header) => header,
+            Err(err) => {
+                log::debug!("skipping unreachable node: {} {}", uri, err);
+                continue;
+            }
+        };
 
         // use the most advanced node as fallback
         if header.number >= fallback_node_num {
@@ -295,6 +353,8 @@ async fn check_nodes(ctx: SharedState, client: hyper::Client<HttpConnector>) {
         nodes.push(uri);
     }
 
+    ctx.ro.metrics.healthy_nodes.set(nodes.len() as f64);
+
     // update nodes
     let mut rw = ctx.rw.lock().await;
     if nodes.is_empty() && fallback_node_uri.is_some() {
@@ -308,14 +368,182 @@ async fn check_nodes(ctx: SharedState, client: hyper::Client<HttpConnector>) {
     rw.nodes = nodes;
 }
 
-async fn event_loop(ctx: SharedState, _client: hyper::Client<HttpConnector>) {
-    // TODO: split sync,mine into own task
+/// Runs `step` until it succeeds, fails with a non-transient error
+/// or `STEP_MAX_ATTEMPTS` are reached.
+/// The state of `ctx` is persisted after each attempt,
+/// the steps only commit consistent progress.
+async fn run_step<F, Fut>(ctx: &SharedState, name: &str, step: F) -> Result<(), CoordinatorError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<(), CoordinatorError>>,
+{
+    let mut backoff = STEP_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        let res = step().await;
+        ctx.save_state().await;
+
+        match res {
+            Err(err) if err.is_transient() && attempt < STEP_MAX_ATTEMPTS => {
+                log::warn!(
+                    "{}: attempt {}/{} failed, retrying in {:?}: {}",
+                    name,
+                    attempt,
+                    STEP_MAX_ATTEMPTS,
+                    backoff,
+                    err
+                );
+                sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(err) => {
+                log::error!("{}: {}", name, err);
+                return Err(err);
+            }
+            Ok(()) => return Ok(()),
+        }
+    }
+}
+
+/// Runs `step` in its own task every `interval` until the process exits.
+/// A stage is woken before `interval` elapsed once `wake` changes
+/// and increments `done` after each successful run.
+/// Failed or panicked runs are logged and counted, the stage continues with the next run.
+fn spawn_stage<F, Fut>(
+    ctx: SharedState,
+    name: &'static str,
+    interval: Duration,
+    mut wake: Option<watch::Receiver<u64>>,
+    done: Option<watch::Sender<u64>>,
+    step: F,
+) -> JoinHandle<()>
+where
+    F: Fn(SharedState) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), CoordinatorError>> + Send + 'static,
+{
+    spawn(async move {
+        let mut runs: u64 = 0;
+        loop {
+            log::debug!("running stage {}", name);
+            match spawn(step(ctx.clone())).await {
+                Err(err) => {
+                    log::error!("{}: {}", name, err);
+                    ctx.ro
+                        .metrics
+                        .stage_failures
+                        .with_label_values(&[name])
+                        .inc();
+                }
+                // already logged by `run_step`
+                Ok(Err(_)) => ctx
+                    .ro
+                    .metrics
+                    .stage_failures
+                    .with_label_values(&[name])
+                    .inc(),
+                Ok(Ok(())) => {
+                    runs += 1;
+                    if let Some(done) = &done {
+                        let _ = done.send(runs);
+                    }
+                }
+            }
+
+            match &mut wake {
+                Some(wake) => {
+                    tokio::select! {
+                        Ok(()) = wake.changed() => {}
+                        _ = sleep(interval) => {}
+                    }
+                }
+                None => sleep(interval).await,
+            }
+        }
+    })
+}
+
+/// Spawns the stages that drive the bridge and the chain progression.
+/// The stages coordinate through `RwState`:
+/// - `sync` processes the L1 and L2 events
+/// - `mine` produces L2 blocks with the messages from L1, woken by `sync`
+/// - `submit` submits and finalizes these blocks on L1, woken by `mine`
+/// - `relay` relays the messages from L2 to L1, woken by `sync`
+/// - `faucet` sends the requested faucet transfers
+///
+/// Each stage sends from its own wallet, see `RoState`.
+/// Stages with the same key share the `TxManager` and the nonces are assigned in order.
+/// Blocks are finalized after their submission, thus `submit` does both in sequence.
+fn spawn_stages(ctx: &SharedState, faucet: Option<Faucet>) -> Vec<JoinHandle<()>> {
+    let (synced_tx, synced_rx) = watch::channel(0u64);
+    let (mined_tx, mined_rx) = watch::channel(0u64);
+
+    let mut handles = vec![
+        spawn_stage(
+            ctx.clone(),
+            "sync",
+            SYNC_INTERVAL,
+            None,
+            Some(synced_tx),
+            |ctx| async move { run_step(&ctx, "sync", || ctx.sync()).await },
+        ),
+        spawn_stage(
+            ctx.clone(),
+            "mine",
+            MINE_INTERVAL,
+            Some(synced_rx.clone()),
+            Some(mined_tx),
+            |ctx| async move { run_step(&ctx, "mine", || ctx.mine()).await },
+        ),
+        spawn_stage(
+            ctx.clone(),
+            "submit",
+            L1_STAGE_INTERVAL,
+            Some(mined_rx),
+            None,
+            |ctx| async move {
+                run_step(&ctx, "submit_blocks", || ctx.submit_blocks()).await?;
+                run_step(&ctx, "finalize_blocks", || ctx.finalize_blocks()).await
+            },
+        ),
+        spawn_stage(
+            ctx.clone(),
+            "relay",
+            L1_STAGE_INTERVAL,
+            Some(synced_rx),
+            None,
+            |ctx| async move { run_step(&ctx, "relay_to_l1", || ctx.relay_to_l1()).await },
+        ),
+    ];
+
+    if let Some(faucet) = faucet {
+        handles.push(spawn_stage(
+            ctx.clone(),
+            "faucet",
+            FAUCET_INTERVAL,
+            None,
+            None,
+            move |ctx| {
+                let faucet = faucet.clone();
+                async move {
+                    // only consume up to 3 items each time
+                    faucet.drain(ctx, 3).await;
+                    Ok(())
+                }
+            },
+        ));
+    }
 
-    ctx.sync().await;
-    ctx.mine().await;
-    ctx.submit_blocks().await;
-    ctx.finalize_blocks().await.expect("finalize_blocks");
-    ctx.relay_to_l1().await;
+    handles
+}
+
+/// The scope required for `method` of the `/rpc` interface,
+/// read-only methods are available with `Scope::Read`.
+fn method_scope(method: &str) -> Scope {
+    match method {
+        "messageStatus" | "pendingMessages" | "droppedMessages" => Scope::Read,
+        _ => Scope::Full,
+    }
 }
 
 async fn handle_method(
@@ -344,6 +572,32 @@ async fn handle_method(
             Ok(serde_json::to_value(config).unwrap())
         }
 
+        // the status of a bridge message by id
+        "messageStatus" => {
+            let id = params.get(0).ok_or("expected message id")?;
+            let id: H256 = serde_json::from_value(id.to_owned()).map_err(|e| e.to_string())?;
+            let status = shared_state.rw.lock().await.message_status(id);
+
+            Ok(serde_json::to_value(status).unwrap())
+        }
+
+        // the queued messages of either `l1ToL2` or `l2ToL1`
+        "pendingMessages" => {
+            let direction = params.get(0).ok_or("expected message direction")?;
+            let direction: MessageDirection =
+                serde_json::from_value(direction.to_owned()).map_err(|e| e.to_string())?;
+            let pending = shared_state.rw.lock().await.pending_messages(direction);
+
+            Ok(serde_json::to_value(pending).unwrap())
+        }
+
+        // the latest messages that were dropped without delivery
+        "droppedMessages" => {
+            let dropped = shared_state.rw.lock().await.dropped_messages.clone();
+
+            Ok(serde_json::to_value(dropped).unwrap())
+        }
+
         _ => Err("this method is not available".to_string()),
     }
 }
@@ -390,42 +644,15 @@ async fn main() {
     }
 
     {
-        let ctx = shared_state.clone();
-        let h1 = spawn(async move {
-            let client = hyper::Client::new();
-            loop {
-                log::debug!("spawning event_loop task");
-
-                {
-                    // the event_loop handles l1/l2 chain events and chain progression
-                    let res = spawn(event_loop(ctx.clone(), client.to_owned())).await;
-
-                    if let Err(err) = res {
-                        log::error!("event_loop: {}", err);
-                    }
-                }
-
-                {
-                    // The faucet shares the same l1 wallet with the event_loop
-                    // above, therefore it should be invoked in serial.
-                    let ctx = ctx.clone();
-                    let faucet = faucet.clone();
-                    let res = spawn(async move {
-                        if let Some(_faucet) = &faucet {
-                            // only consume up to 3 items each time
-                            _faucet.drain(ctx, 3).await;
-                        }
-                    })
-                    .await;
-
-                    if let Err(err) = res {
-                        log::error!("faucet: {}", err);
-                    }
-                }
-
-                sleep(EVENT_LOOP_COOLDOWN).await;
-            }
-        });
+        // the other stages rely on the synced bridge state,
+        // e.g. to not relay messages again after a restart
+        while run_step(&shared_state, "sync", || shared_state.sync())
+            .await
+            .is_err()
+        {
+            sleep(SYNC_INTERVAL).await;
+        }
+        let mut handles = spawn_stages(&shared_state, faucet);
 
         let ctx = shared_state.clone();
         let h2 = spawn(async move {
@@ -442,9 +669,13 @@ async fn main() {
             }
         });
 
+        handles.push(h2);
+
         // wait for all tasks
-        if tokio::try_join!(h1, h2).is_err() {
-            panic!("unexpected task error");
+        for handle in handles {
+            if handle.await.is_err() {
+                panic!("unexpected task error");
+            }
         }
     }
 }
@@ -4,6 +4,9 @@ use hyper::Uri;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use std::net::SocketAddr;
+use zkevm_common::auth::AuthConfig;
+use zkevm_common::circuit_autogen::CIRCUIT_CONFIGS;
+use zkevm_common::prover::MAX_AGGREGATION_BLOCKS;
 
 #[serde_as]
 #[derive(Parser, Deserialize, Serialize, Clone, Debug)]
@@ -49,8 +52,31 @@ pub struct Config {
 
     #[clap(long, env = "COORDINATOR_L1_PRIV")]
     /// Private key for Ethereum L1 wallet.
+    /// Submits and finalizes blocks, also the default for the other keys.
+    /// Each key can also be `keystore:<path>` for an encrypted keystore file
+    /// or `remote:<url>` for an external signer with `eth_signTransaction`.
     pub l1_priv: String,
 
+    #[clap(long, env = "COORDINATOR_KEYSTORE_PASSWORD")]
+    #[serde(skip)]
+    /// The password for keys of the form `keystore:<path>`.
+    pub keystore_password: Option<String>,
+
+    #[clap(long, env = "COORDINATOR_L1_RELAYER_PRIV")]
+    #[serde(skip)]
+    /// Private key for relaying messages to L1, defaults to `l1_priv`.
+    pub l1_relayer_priv: Option<String>,
+
+    #[clap(long, env = "COORDINATOR_L2_PRIV")]
+    #[serde(skip)]
+    /// Private key for delivering messages on L2, defaults to `l1_priv`.
+    pub l2_priv: Option<String>,
+
+    #[clap(long, env = "COORDINATOR_FAUCET_PRIV")]
+    #[serde(skip)]
+    /// Private key for the L1 faucet transfers, defaults to `l1_priv`.
+    pub faucet_priv: Option<String>,
+
     #[clap(long, env = "COORDINATOR_L2_RPC_URL")]
     #[serde_as(as = "DisplayFromStr")]
     /// L2 RPC node in http URL format.
@@ -75,13 +101,158 @@ pub struct Config {
     /// Signals the prover to aggregate the circuit proof
     pub aggregate_proof: bool,
 
+    #[clap(
+        long,
+        env = "COORDINATOR_FINALIZE_BATCH",
+        default_value_t = 1,
+        value_parser = parse_finalize_batch
+    )]
+    /// The number of blocks to finalize with one proof in one L1 transaction,
+    /// at most `MAX_AGGREGATION_BLOCKS`. Requires `aggregate_proof`,
+    /// blocks fall back to single block finalization otherwise.
+    pub finalize_batch: usize,
+
     #[clap(long, env = "COORDINATOR_UNSAFE_RPC", default_value_t = false)]
     /// Allow unsafe rpc methods of the coordinator if true
     pub unsafe_rpc: bool,
+
+    #[clap(long, env = "COORDINATOR_SUBMIT_BATCH", default_value_t = false)]
+    /// Submit multiple blocks per L1 transaction.
+    pub submit_batch: bool,
+
+    #[clap(
+        long,
+        env = "COORDINATOR_SUBMIT_BATCH_CALLDATA_LIMIT",
+        default_value_t = 120_000
+    )]
+    /// The maximum size in bytes of the block witnesses in a batch.
+    pub submit_batch_calldata_limit: usize,
+
+    #[clap(
+        long,
+        env = "COORDINATOR_SUBMIT_BATCH_GAS_LIMIT",
+        default_value_t = 15_000_000
+    )]
+    /// The maximum L1 gas of a batch transaction.
+    pub submit_batch_gas_limit: u64,
+
+    #[clap(long, env = "COORDINATOR_L1_CONFIRMATIONS", default_value_t = 0)]
+    /// The number of blocks behind the L1 head that are not synced yet.
+    /// Also the number of confirmations for L1 transactions.
+    pub l1_confirmations: u64,
+
+    #[clap(long, env = "COORDINATOR_TX_BUMP_TIMEOUT", default_value_t = 30)]
+    /// The number of seconds until a pending L1 transaction is replaced with higher fees.
+    pub tx_bump_timeout: u64,
+
+    #[clap(long, env = "COORDINATOR_TX_MAX_BUMPS", default_value_t = 3)]
+    /// The number of fee bumps of a L1 transaction before giving up.
+    pub tx_max_bumps: u32,
+
+    #[clap(
+        long,
+        env = "COORDINATOR_MESSAGE_INCLUSION_TIMEOUT",
+        default_value_t = 300
+    )]
+    /// The number of seconds a L1 > L2 message may wait in the queue.
+    /// Overdue messages are delivered before any transactions of the pool.
+    pub message_inclusion_timeout: u64,
+
+    #[clap(long, env = "COORDINATOR_BLOCK_INTERVAL", default_value_t = 3)]
+    /// The target L2 block time in seconds.
+    /// Transactions of the pool wait until the previous block is this old.
+    pub block_interval: u64,
+
+    #[clap(long, env = "COORDINATOR_EMPTY_BLOCK_INTERVAL")]
+    /// Seals a block after this many seconds without a block,
+    /// even if it is empty or has less than `min_block_txs` transactions.
+    pub empty_block_interval: Option<u64>,
+
+    #[clap(long, env = "COORDINATOR_MIN_BLOCK_TXS", default_value_t = 1)]
+    /// The number of pending transactions required to seal a block.
+    pub min_block_txs: u64,
+
+    #[clap(long, env = "COORDINATOR_MAX_BLOCK_GAS")]
+    /// The maximum gas used of a L2 block, see `block_limits`.
+    pub max_block_gas: Option<u64>,
+
+    #[clap(long, env = "COORDINATOR_MAX_BLOCK_TXS")]
+    /// The maximum number of transactions of a L2 block, see `block_limits`.
+    pub max_block_txs: Option<usize>,
+
+    #[clap(long, env = "COORDINATOR_LOG_WINDOW", default_value_t = 1000)]
+    /// The maximum number of blocks per `eth_getLogs` request.
+    pub log_window: u64,
+
+    #[clap(long, env = "COORDINATOR_DATA_DIR")]
+    /// A directory to persist the sync progress and message queues in.
+    /// The state is only kept in memory if not set.
+    pub data_dir: Option<String>,
+
+    #[clap(long, env = "COORDINATOR_RPC_AUTH_TOKEN")]
+    #[serde(skip)]
+    /// A bearer token that grants access to all methods of the `/rpc` interface.
+    /// The interface is public if neither token is set.
+    pub rpc_auth_token: Option<String>,
+
+    #[clap(long, env = "COORDINATOR_RPC_AUTH_READ_TOKEN")]
+    #[serde(skip)]
+    /// A bearer token that grants access to read-only methods of the `/rpc` interface.
+    pub rpc_auth_read_token: Option<String>,
+
+    #[clap(long, env = "COORDINATOR_PROVER_RPCD_AUTH_TOKEN")]
+    #[serde(skip)]
+    /// The bearer token for the requests to `prover_rpcd_url`.
+    pub prover_rpcd_auth_token: Option<String>,
 }
 
 impl Config {
     pub fn from_env() -> Self {
         Self::parse_from(std::env::args().skip(usize::MAX))
     }
+
+    /// Returns the maximum gas used and number of transactions of a L2 block.
+    /// Both are bound by the largest circuit of the prover.
+    pub fn block_limits(&self) -> (u64, usize) {
+        let largest = CIRCUIT_CONFIGS.last().expect("circuit config");
+        let max_gas = largest.block_gas_limit as u64;
+        let max_txs = largest.max_txs;
+
+        (
+            self.max_block_gas.map_or(max_gas, |e| e.min(max_gas)),
+            self.max_block_txs.map_or(max_txs, |e| e.min(max_txs)),
+        )
+    }
+
+    /// Returns `true` if a block should be sealed from the transaction pool
+    /// `elapsed` seconds after the previous block, with `pending_txs` transactions in the pool.
+    pub fn seal_block(&self, elapsed: u64, pending_txs: u64) -> bool {
+        let due = elapsed >= self.block_interval && pending_txs >= self.min_block_txs;
+
+        due || self.heartbeat_due(elapsed)
+    }
+
+    /// Returns `true` if a block should be sealed even if it is empty,
+    /// see `empty_block_interval`.
+    pub fn heartbeat_due(&self, elapsed: u64) -> bool {
+        self.empty_block_interval
+            .map_or(false, |interval| elapsed >= interval)
+    }
+
+    pub fn rpc_auth(&self) -> AuthConfig {
+        AuthConfig {
+            token: self.rpc_auth_token.clone(),
+            read_token: self.rpc_auth_read_token.clone(),
+        }
+    }
+}
+
+/// Only batch sizes with a deployed aggregation verifier are valid.
+fn parse_finalize_batch(value: &str) -> Result<usize, String> {
+    let batch: usize = value.parse().map_err(|e| format!("{}", e))?;
+    if batch == 0 || batch > MAX_AGGREGATION_BLOCKS {
+        return Err(format!("must be between 1 and {}", MAX_AGGREGATION_BLOCKS));
+    }
+
+    Ok(batch)
 }
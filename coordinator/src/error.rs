@@ -0,0 +1,53 @@
+use std::fmt;
+use zkevm_common::json_rpc::JsonRpcRequestError;
+
+/// Errors of the coordinator steps, classified by how to recover from them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CoordinatorError {
+    /// A request to a L1/L2 node or the prover failed, retrying may succeed.
+    Rpc(String),
+    /// A transaction or call reverted, retrying the same request won't help.
+    Revert(String),
+    /// Unexpected chain or coordinator state.
+    Invariant(String),
+}
+
+impl CoordinatorError {
+    /// Returns true if retrying the failed step may succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Rpc(_))
+    }
+}
+
+impl fmt::Display for CoordinatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rpc(err) => write!(f, "rpc: {}", err),
+            Self::Revert(err) => write!(f, "revert: {}", err),
+            Self::Invariant(err) => write!(f, "invariant: {}", err),
+        }
+    }
+}
+
+/// Untyped errors are failed requests, see `From<JsonRpcRequestError>`
+/// and `From<TxError>` for the classification of reverts.
+impl From<String> for CoordinatorError {
+    fn from(err: String) -> Self {
+        Self::Rpc(err)
+    }
+}
+
+impl From<JsonRpcRequestError> for CoordinatorError {
+    fn from(err: JsonRpcRequestError) -> Self {
+        match err {
+            JsonRpcRequestError::Response(err) if err.is_revert() => Self::Revert(err.message),
+            err => Self::Rpc(err.to_string()),
+        }
+    }
+}
+
+impl From<CoordinatorError> for String {
+    fn from(err: CoordinatorError) -> Self {
+        err.to_string()
+    }
+}
@@ -3,7 +3,6 @@ use std::sync::Arc;
 
 use ethers_core::types::Address;
 use ethers_core::types::U256;
-use ethers_signers::Signer;
 
 use tokio::spawn;
 use tokio::sync::Mutex;
@@ -22,16 +21,15 @@ impl Faucet {
         }
     }
 
-    /// Iterates over `queue` and sends ETH with the `shared_state.ro.l1_wallet`.
-    /// To avoid replacing transactions or invoking other race conditions,
-    /// this function should not be run in parallel with any other `SharedState` tasks.
+    /// Iterates over `queue` and sends ETH with `shared_state.ro.faucet_tx_manager`.
+    /// The transfers don't conflict with the transactions of other `SharedState` tasks.
     /// Only consumes up to `max_items` items from the queue each time.
     pub async fn drain(&self, shared_state: SharedState, max_items: usize) {
         let mut queue = self.queue.lock().await;
         let mut remaining_balance: U256 = shared_state
             .request_l1(
                 "eth_getBalance",
-                (shared_state.ro.l1_wallet.address(), "latest"),
+                (shared_state.ro.faucet_tx_manager.address(), "latest"),
             )
             .await
             .expect("l1 balance");
@@ -64,7 +62,12 @@ impl Faucet {
                 let shared_state = shared_state.clone();
                 let res = spawn(async move {
                     shared_state
-                        .transaction_to_l1(Some(receiver), faucet_amount, vec![])
+                        .transaction_to_l1_from(
+                            &shared_state.ro.faucet_tx_manager,
+                            Some(receiver),
+                            faucet_amount,
+                            vec![],
+                        )
                         .await
                         .expect("receipt");
                 })
@@ -82,5 +85,6 @@ impl Faucet {
 
         // drain all successful transfers
         queue.drain(0..i);
+        shared_state.ro.metrics.faucet_queue.set(queue.len() as f64);
     }
 }
@@ -1,6 +1,12 @@
 pub mod config;
+pub mod error;
 pub mod faucet;
+pub mod log_fetcher;
 pub mod macros;
+pub mod metrics;
 pub mod shared_state;
+pub mod signer;
+pub mod state_store;
 pub mod structs;
+pub mod tx_manager;
 pub mod utils;
@@ -0,0 +1,179 @@
+use crate::utils::RPC_REQUEST_TIMEOUT;
+use ethers_core::types::{Filter, Log, U64};
+use hyper::client::HttpConnector;
+use hyper::Uri;
+use std::cmp;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use zkevm_common::json_rpc::jsonrpc_call;
+use zkevm_common::json_rpc::JsonRpcError;
+use zkevm_common::json_rpc::JsonRpcRequestError;
+
+/// The logs of a window of blocks.
+pub struct LogRange {
+    pub from: U64,
+    pub to: U64,
+    pub logs: Vec<Log>,
+    /// The last block whose logs are complete and should be used as the sync cursor.
+    /// Can be `to - 1` if the window ends at the chain head,
+    /// see `LogFetcher::next`.
+    pub synced: U64,
+}
+
+/// Fetches logs with `eth_getLogs` in windows of blocks.
+/// The window grows after each successful request up to `max_window`
+/// and shrinks if a request exceeds the block range or result limits of the provider.
+/// The window doesn't grow past a size that exceeded a limit again
+/// until `LIMIT_RECOVERY` requests in a row succeeded at that size.
+/// Rate limited requests are retried without changing the window.
+pub struct LogFetcher {
+    /// The maximum number of blocks per request.
+    pub max_window: u64,
+    /// The number of blocks behind the chain head that are not fetched yet.
+    pub confirmations: u64,
+    window: AtomicU64,
+    /// The window doesn't grow past this once a request exceeded a limit.
+    limit: AtomicU64,
+    /// The number of successful requests in a row with a window at `limit`.
+    successes: AtomicU64,
+}
+
+/// The number of successful requests in a row at the limit after which
+/// the limit is raised again.
+pub const LIMIT_RECOVERY: u64 = 32;
+/// The number of retries of a rate limited request.
+const RATE_LIMIT_RETRIES: u32 = 3;
+/// The delay before the first retry of a rate limited request, doubled for each retry.
+const RATE_LIMIT_DELAY: Duration = Duration::from_millis(500);
+
+impl LogFetcher {
+    pub fn new(max_window: u64, confirmations: u64) -> Self {
+        let max_window = cmp::max(max_window, 1);
+
+        Self {
+            max_window,
+            confirmations,
+            window: AtomicU64::new(cmp::min(max_window, 16)),
+            limit: AtomicU64::new(max_window),
+            successes: AtomicU64::new(0),
+        }
+    }
+
+    /// The current window size.
+    pub fn window(&self) -> u64 {
+        self.window.load(Ordering::Relaxed)
+    }
+
+    /// Returns the logs matching `filter` for the next window starting at `from`
+    /// or `None` if `from` is past `latest - confirmations`.
+    ///
+    /// Some nodes don't return the logs of the latest block right away.
+    /// If the window ends at `latest` and contains no logs for that block,
+    /// then `LogRange::synced` excludes the latest block so that it is fetched again
+    /// by the next sync.
+    pub async fn next(
+        &self,
+        client: &hyper::Client<HttpConnector>,
+        uri: &Uri,
+        filter: &Filter,
+        from: U64,
+        latest: U64,
+    ) -> Result<Option<LogRange>, String> {
+        let safe_head = latest.saturating_sub(U64::from(self.confirmations));
+        if from > safe_head {
+            return Ok(None);
+        }
+
+        let mut retries = 0;
+        loop {
+            let window = self.window();
+            let to = cmp::min(from + window - 1, safe_head);
+            log::trace!("fetching logs from={} to={} window={}", from, to, window);
+
+            let filter = filter.clone().from_block(from).to_block(to);
+            let res: Result<Vec<Log>, JsonRpcRequestError> =
+                jsonrpc_call(RPC_REQUEST_TIMEOUT, client, uri, "eth_getLogs", [&filter]).await;
+
+            match res {
+                Err(JsonRpcRequestError::Response(err))
+                    if retries < RATE_LIMIT_RETRIES && is_rate_limit_error(&err) =>
+                {
+                    log::debug!("eth_getLogs: rate limited, retrying: {}", err.message);
+                    tokio::time::sleep(RATE_LIMIT_DELAY * 2u32.pow(retries)).await;
+                    retries += 1;
+                }
+                Err(JsonRpcRequestError::Response(err))
+                    if window > 1 && !is_rate_limit_error(&err) && is_limit_error(&err) =>
+                {
+                    log::debug!(
+                        "eth_getLogs: shrinking window of {}: {}",
+                        window,
+                        err.message
+                    );
+                    self.limit.fetch_min(window / 2, Ordering::Relaxed);
+                    self.window.store(window / 2, Ordering::Relaxed);
+                    self.successes.store(0, Ordering::Relaxed);
+                }
+                Err(err) => return Err(err.into()),
+                Ok(logs) => {
+                    let mut limit = self.limit.load(Ordering::Relaxed);
+                    if window >= limit && limit < self.max_window {
+                        let successes = self.successes.fetch_add(1, Ordering::Relaxed) + 1;
+                        if successes >= LIMIT_RECOVERY {
+                            limit = cmp::min(limit * 2, self.max_window);
+                            log::debug!("eth_getLogs: raising window limit to {}", limit);
+                            self.limit.store(limit, Ordering::Relaxed);
+                            self.successes.store(0, Ordering::Relaxed);
+                        }
+                    }
+                    self.window
+                        .store(cmp::min(window * 2, limit), Ordering::Relaxed);
+
+                    let head_complete =
+                        to < latest || logs.iter().any(|e| e.block_number == Some(to));
+                    let synced = if head_complete { to } else { to - 1 };
+
+                    return Ok(Some(LogRange {
+                        from,
+                        to,
+                        logs,
+                        synced,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+/// Returns true if `err` says that the provider rate limits the requests,
+/// e.g. `-32005 project ID request rate exceeded` or `429 Too Many Requests`.
+fn is_rate_limit_error(err: &JsonRpcError) -> bool {
+    let message = err.message.to_lowercase();
+    err.code == 429
+        || ["rate limit", "rate exceeded", "too many requests"]
+            .iter()
+            .any(|e| message.contains(e))
+}
+
+/// Returns true if `err` is the response to an `eth_getLogs` request
+/// that exceeds the block range or the number of results the provider allows.
+/// Providers don't agree on an error code for this, thus the message is checked as well.
+fn is_limit_error(err: &JsonRpcError) -> bool {
+    // `limit exceeded`
+    if err.code == -32005 {
+        return true;
+    }
+
+    let message = err.message.to_lowercase();
+    [
+        "range",
+        "limit",
+        "too many",
+        "too large",
+        "more than",
+        "exceed",
+    ]
+    .iter()
+    .any(|e| message.contains(e))
+}
@@ -0,0 +1,146 @@
+use prometheus::Counter;
+use prometheus::CounterVec;
+use prometheus::Gauge;
+use prometheus::GaugeVec;
+use prometheus::Histogram;
+use prometheus::HistogramOpts;
+use prometheus::HistogramVec;
+use prometheus::Opts;
+use prometheus::Registry;
+use zkevm_common::metrics::*;
+
+/// Buckets for the gas used by a single transaction.
+pub const GAS_BUCKETS: [f64; 10] = [
+    50_000.0,
+    100_000.0,
+    200_000.0,
+    500_000.0,
+    1_000_000.0,
+    2_000_000.0,
+    5_000_000.0,
+    10_000_000.0,
+    15_000_000.0,
+    30_000_000.0,
+];
+
+/// Metrics of the coordinator, exposed via `/metrics`.
+/// Updated by the coordinator stages and `check_nodes`.
+pub struct CoordinatorMetrics {
+    pub block_gap: GaugeVec,
+    pub message_queue: GaugeVec,
+    pub l1_gas_used: HistogramVec,
+    pub l1_gas_per_block: Histogram,
+    pub proof_latency: Histogram,
+    pub proof_request_failures: Counter,
+    pub healthy_nodes: Gauge,
+    pub faucet_queue: Gauge,
+    pub stage_failures: CounterVec,
+    registry: Registry,
+}
+
+impl Default for CoordinatorMetrics {
+    fn default() -> Self {
+        let registry = Registry::new();
+
+        Self {
+            block_gap: register(
+                &registry,
+                GaugeVec::new(
+                    Opts::new(
+                        "coordinator_block_gap",
+                        "Number of blocks between head, safe and finalized",
+                    ),
+                    &["range"],
+                )
+                .unwrap(),
+            ),
+            message_queue: register(
+                &registry,
+                GaugeVec::new(
+                    Opts::new(
+                        "coordinator_message_queue_length",
+                        "Number of pending bridge messages by queue",
+                    ),
+                    &["queue"],
+                )
+                .unwrap(),
+            ),
+            l1_gas_used: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new(
+                        "coordinator_l1_gas_used",
+                        "Gas used per L1 transaction by bridge method",
+                    )
+                    .buckets(GAS_BUCKETS.to_vec()),
+                    &["method"],
+                )
+                .unwrap(),
+            ),
+            l1_gas_per_block: register(
+                &registry,
+                Histogram::with_opts(
+                    HistogramOpts::new(
+                        "coordinator_l1_gas_per_block",
+                        "L1 gas used for the submission per L2 block",
+                    )
+                    .buckets(GAS_BUCKETS.to_vec()),
+                )
+                .unwrap(),
+            ),
+            proof_latency: register(
+                &registry,
+                Histogram::with_opts(
+                    HistogramOpts::new(
+                        "coordinator_proof_latency_seconds",
+                        "Time from the first proof request of a block until the proof is available",
+                    )
+                    .buckets(DURATION_BUCKETS.to_vec()),
+                )
+                .unwrap(),
+            ),
+            proof_request_failures: register(
+                &registry,
+                Counter::new(
+                    "coordinator_proof_request_failures_total",
+                    "Failed proof requests to the prover",
+                )
+                .unwrap(),
+            ),
+            healthy_nodes: register(
+                &registry,
+                Gauge::new(
+                    "coordinator_healthy_nodes",
+                    "Number of L2 rpc nodes that are in sync with the chain head",
+                )
+                .unwrap(),
+            ),
+            faucet_queue: register(
+                &registry,
+                Gauge::new(
+                    "coordinator_faucet_queue_length",
+                    "Number of pending faucet requests",
+                )
+                .unwrap(),
+            ),
+            stage_failures: register(
+                &registry,
+                CounterVec::new(
+                    Opts::new(
+                        "coordinator_stage_failures_total",
+                        "Failed runs of the coordinator stages by stage",
+                    ),
+                    &["stage"],
+                )
+                .unwrap(),
+            ),
+            registry,
+        }
+    }
+}
+
+impl CoordinatorMetrics {
+    pub fn render(&self) -> String {
+        render(&self.registry)
+    }
+}
@@ -1,5 +1,14 @@
 use crate::config::Config;
+use crate::error::CoordinatorError;
+use crate::log_fetcher::LogFetcher;
+use crate::metrics::CoordinatorMetrics;
+use crate::signer::TxSigner;
+use crate::state_store::Snapshot;
+use crate::state_store::StateStore;
+use crate::state_store::SNAPSHOT_VERSION;
 use crate::structs::*;
+use crate::tx_manager::TxError;
+use crate::tx_manager::TxManager;
 use crate::utils::*;
 use ethers_core::abi::Abi;
 use ethers_core::abi::AbiParser;
@@ -13,22 +22,27 @@ use ethers_core::types::{
 };
 use ethers_core::utils::keccak256;
 use ethers_core::utils::rlp;
-use ethers_signers::LocalWallet;
-use ethers_signers::Signer;
 use hyper::client::HttpConnector;
 use hyper::Uri;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::cmp;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 use std::time::SystemTime;
+use tokio::sync::watch;
 use tokio::sync::Mutex;
+use zkevm_common::json_rpc::jsonrpc_call;
+use zkevm_common::json_rpc::jsonrpc_call_with_token;
 use zkevm_common::json_rpc::jsonrpc_request;
-use zkevm_common::json_rpc::jsonrpc_request_client;
+use zkevm_common::json_rpc::JsonRpcRequestError;
 use zkevm_common::prover::ProofRequestOptions;
 use zkevm_common::prover::Proofs;
+use zkevm_common::prover::MAX_AGGREGATION_BLOCKS;
 
 pub struct RoState {
     pub l2_message_deliverer_addr: Address,
@@ -40,17 +54,68 @@ pub struct RoState {
     pub message_delivered_topic: H256,
 
     pub http_client: hyper::Client<HttpConnector>,
-    pub l1_wallet: LocalWallet,
-    pub l2_wallet: LocalWallet,
+    /// submits and finalizes blocks
+    pub l1_wallet: TxSigner,
+    /// delivers messages on L2
+    pub l2_wallet: TxSigner,
+    /// sends the transactions of `l1_wallet`
+    pub l1_tx_manager: Arc<TxManager>,
+    /// relays messages to L1
+    pub l1_relayer_tx_manager: Arc<TxManager>,
+    /// sends the faucet transfers
+    pub faucet_tx_manager: Arc<TxManager>,
 
     pub bridge_abi: Abi,
+
+    pub metrics: CoordinatorMetrics,
+    pub state_store: Option<Arc<StateStore>>,
+    // the latest snapshot for `state_store`, see `SharedState::save_state`
+    pub state_store_tx: Option<watch::Sender<Arc<Snapshot>>>,
+    pub l1_log_fetcher: LogFetcher,
+    pub l2_log_fetcher: LogFetcher,
 }
 
 impl RoState {
     pub async fn new(config: &Config) -> Self {
-        let l1_wallet = get_wallet(&config.l1_rpc_url, &config.l1_priv).await;
-        // TODO: support different keys for L1 and L2
-        let l2_wallet = get_wallet(&config.l2_rpc_url, &config.l1_priv).await;
+        let password = config.keystore_password.as_deref();
+        let l1_wallet = get_wallet(&config.l1_rpc_url, &config.l1_priv, password).await;
+        let l1_relayer_wallet = get_wallet(
+            &config.l1_rpc_url,
+            config.l1_relayer_priv.as_ref().unwrap_or(&config.l1_priv),
+            password,
+        )
+        .await;
+        let faucet_wallet = get_wallet(
+            &config.l1_rpc_url,
+            config.faucet_priv.as_ref().unwrap_or(&config.l1_priv),
+            password,
+        )
+        .await;
+        let l2_wallet = get_wallet(
+            &config.l2_rpc_url,
+            config.l2_priv.as_ref().unwrap_or(&config.l1_priv),
+            password,
+        )
+        .await;
+
+        // wallets with the same key share the nonce
+        let mut tx_managers: Vec<Arc<TxManager>> = Vec::new();
+        let mut tx_manager = |wallet: &TxSigner| {
+            if let Some(tx_manager) = tx_managers.iter().find(|e| e.address() == wallet.address()) {
+                return tx_manager.clone();
+            }
+            let tx_manager = Arc::new(TxManager::new(
+                wallet.clone(),
+                Duration::from_secs(config.tx_bump_timeout),
+                config.tx_max_bumps,
+                config.l1_confirmations,
+            ));
+            tx_managers.push(tx_manager.clone());
+            tx_manager
+        };
+        let l1_tx_manager = tx_manager(&l1_wallet);
+        let l1_relayer_tx_manager = tx_manager(&l1_relayer_wallet);
+        let faucet_tx_manager = tx_manager(&faucet_wallet);
 
         let abi = get_abi();
 
@@ -58,6 +123,26 @@ impl RoState {
         let block_finalized_topic = abi.event("BlockFinalized").unwrap().signature();
         let message_dispatched_topic = abi.event("MessageDispatched").unwrap().signature();
         let message_delivered_topic = abi.event("MessageDelivered").unwrap().signature();
+        let state_store = config
+            .data_dir
+            .as_ref()
+            .map(|dir| Arc::new(StateStore::new(dir).expect("state store")));
+        let state_store_tx = state_store.clone().map(|state_store| {
+            // writes the latest snapshot, snapshots sent during a write are coalesced
+            let (tx, mut rx) = watch::channel(Arc::new(RwState::default().snapshot()));
+            tokio::spawn(async move {
+                while rx.changed().await.is_ok() {
+                    let snapshot = rx.borrow().clone();
+                    let state_store = state_store.clone();
+                    match tokio::task::spawn_blocking(move || state_store.save(&snapshot)).await {
+                        Err(err) => log::error!("state_store: failed to save state: {}", err),
+                        Ok(Err(err)) => log::error!("state_store: failed to save state: {}", err),
+                        Ok(Ok(())) => {}
+                    }
+                }
+            });
+            tx
+        });
 
         RoState {
             l2_message_deliverer_addr: "0x0000000000000000000000000000000000010000"
@@ -73,9 +158,18 @@ impl RoState {
             message_delivered_topic,
 
             http_client: hyper::Client::new(),
+            l1_tx_manager,
+            l1_relayer_tx_manager,
+            faucet_tx_manager,
             l1_wallet,
             l2_wallet,
             bridge_abi: abi,
+            metrics: CoordinatorMetrics::default(),
+            state_store,
+            state_store_tx,
+            l1_log_fetcher: LogFetcher::new(config.log_window, config.l1_confirmations),
+            // the coordinator is the only block producer of L2
+            l2_log_fetcher: LogFetcher::new(config.log_window, 0),
         }
     }
 }
@@ -88,9 +182,28 @@ pub struct RwState {
     pub l1_last_sync_block: U64,
     pub l2_last_sync_block: U64,
     pub l1_message_queue: VecDeque<MessageBeacon>,
+    /// the inclusion deadline of each message in `l1_message_queue`
+    pub l1_message_deadlines: HashMap<H256, u64>,
+    /// the latest messages that were removed from either queue without delivery
+    pub dropped_messages: VecDeque<DroppedMessage>,
+    /// the bridge events of the latest messages in both directions
+    pub message_records: VecDeque<MessageRecord>,
     pub l2_delivered_messages: Vec<H256>,
     pub l2_message_queue: Vec<MessageBeacon>,
     pub l1_delivered_messages: Vec<H256>,
+    /// the processed L1 blocks with bridge events and the last synced block,
+    /// used to detect and roll back L1 reorgs
+    pub l1_blocks: VecDeque<L1BlockRecord>,
+    /// the time of the first proof request for a block, used for `CoordinatorMetrics::proof_latency`
+    pub proof_requested_at: HashMap<U64, Instant>,
+    /// L2 blocks that were submitted to L1 but whose `BlockSubmitted` event is not synced yet.
+    /// Only `sync` advances `chain_state`, these are skipped by `submit_blocks` in the meantime.
+    pub pending_submissions: HashSet<H256>,
+    /// Same as `pending_submissions` for `BlockFinalized` and `finalize_blocks`.
+    pub pending_finalizations: HashSet<H256>,
+    /// Pool transactions that don't fit into a provable block on their own,
+    /// skipped by `prepare_provable_block` as long as they are in the pool.
+    pub unprovable_txs: HashSet<H256>,
 
     /// keeps track of the timestamp used for preparing the last block
     _prev_timestamp: u64,
@@ -110,15 +223,256 @@ impl Default for RwState {
             l1_last_sync_block: U64::zero(),
             l2_last_sync_block: U64::zero(),
             l1_message_queue: VecDeque::new(),
+            l1_message_deadlines: HashMap::new(),
+            dropped_messages: VecDeque::new(),
+            message_records: VecDeque::new(),
             l2_delivered_messages: Vec::new(),
             l2_message_queue: Vec::new(),
             l1_delivered_messages: Vec::new(),
+            l1_blocks: VecDeque::new(),
+            proof_requested_at: HashMap::new(),
+            pending_submissions: HashSet::new(),
+            pending_finalizations: HashSet::new(),
+            unprovable_txs: HashSet::new(),
 
             _prev_timestamp: 0,
         }
     }
 }
 
+impl RwState {
+    /// Returns the state that is required to resume after a restart.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            chain_state: self.chain_state,
+            l1_last_sync_block: self.l1_last_sync_block,
+            l2_last_sync_block: self.l2_last_sync_block,
+            l1_message_queue: self.l1_message_queue.clone(),
+            l1_message_deadlines: self.l1_message_deadlines.clone(),
+            dropped_messages: self.dropped_messages.clone(),
+            message_records: self.message_records.clone(),
+            l2_delivered_messages: self.l2_delivered_messages.clone(),
+            l2_message_queue: self.l2_message_queue.clone(),
+            l1_delivered_messages: self.l1_delivered_messages.clone(),
+            l1_blocks: self.l1_blocks.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.chain_state = snapshot.chain_state;
+        self.l1_last_sync_block = snapshot.l1_last_sync_block;
+        self.l2_last_sync_block = snapshot.l2_last_sync_block;
+        self.l1_message_queue = snapshot.l1_message_queue;
+        self.l1_message_deadlines = snapshot.l1_message_deadlines;
+        self.dropped_messages = snapshot.dropped_messages;
+        self.message_records = snapshot.message_records;
+        self.l2_delivered_messages = snapshot.l2_delivered_messages;
+        self.l2_message_queue = snapshot.l2_message_queue;
+        self.l1_delivered_messages = snapshot.l1_delivered_messages;
+        self.l1_blocks = snapshot.l1_blocks;
+        // snapshots of previous versions kept every message
+        truncate_front(&mut self.l1_delivered_messages, DELIVERED_MESSAGES_HISTORY);
+        truncate_front(&mut self.l2_delivered_messages, DELIVERED_MESSAGES_HISTORY);
+    }
+
+    /// Rolls back the changes of the processed L1 blocks after `ancestor`
+    /// and resets the sync progress to `ancestor`.
+    /// Messages that were already delivered to L2 can't be rolled back.
+    pub fn rollback_l1(&mut self, ancestor: U64) {
+        while self.l1_blocks.back().map(|e| e.number > ancestor) == Some(true) {
+            let record = self.l1_blocks.pop_back().unwrap();
+            log::info!("rolling back L1 block {} {:?}", record.number, record.hash);
+
+            if let Some(block_hash) = record.prev_safe_block_hash {
+                self.chain_state.safe_block_hash = block_hash;
+            }
+            if let Some(block_hash) = record.prev_finalized_block_hash {
+                self.chain_state.finalized_block_hash = block_hash;
+            }
+            self.l1_message_queue
+                .retain(|e| !record.l1_messages.contains(&e.id));
+            self.l1_delivered_messages
+                .retain(|e| !record.l1_delivered_messages.contains(e));
+            self.l2_message_queue
+                .retain(|e| !record.l2_messages.contains(&e.id));
+        }
+
+        let queue = &self.l1_message_queue;
+        self.l1_message_deadlines
+            .retain(|id, _| queue.iter().any(|e| &e.id == id));
+        // the transactions may be reorged as well, submit or finalize these blocks again
+        // unless `sync` sees their events on the canonical chain
+        self.pending_submissions.clear();
+        self.pending_finalizations.clear();
+
+        self.l1_last_sync_block = cmp::min(self.l1_last_sync_block, ancestor);
+    }
+
+    /// Returns the entry of `message_records` for `id`, inserts it if missing.
+    fn message_record(&mut self, id: H256, direction: MessageDirection) -> &mut MessageRecord {
+        match self.message_records.iter().position(|e| e.id == id) {
+            Some(pos) => &mut self.message_records[pos],
+            None => {
+                if self.message_records.len() == MESSAGE_RECORDS_HISTORY {
+                    self.message_records.pop_front();
+                }
+                self.message_records.push_back(MessageRecord {
+                    id,
+                    direction,
+                    dispatch_tx: None,
+                    dispatched_at: None,
+                    delivery_tx: None,
+                    delivered_at: None,
+                });
+                self.message_records.back_mut().unwrap()
+            }
+        }
+    }
+
+    /// Records the `MessageDispatched` event of `id` in `tx_hash` at `timestamp`.
+    pub fn record_message_dispatch(
+        &mut self,
+        id: H256,
+        direction: MessageDirection,
+        tx_hash: Option<H256>,
+        timestamp: u64,
+    ) {
+        let record = self.message_record(id, direction);
+        record.dispatch_tx = tx_hash;
+        record.dispatched_at = Some(timestamp);
+    }
+
+    /// Records the `MessageDelivered` event of `id` in `tx_hash` at `timestamp`.
+    pub fn record_message_delivery(
+        &mut self,
+        id: H256,
+        direction: MessageDirection,
+        tx_hash: Option<H256>,
+        timestamp: u64,
+    ) {
+        let record = self.message_record(id, direction);
+        record.delivery_tx = tx_hash;
+        record.delivered_at = Some(timestamp);
+    }
+
+    /// Orders `l1_message_queue` by inclusion deadline and returns its length.
+    /// Messages without a deadline, e.g. from an older snapshot, are due at `now`.
+    pub fn order_l1_message_queue(&mut self, now: u64) -> usize {
+        for msg in self.l1_message_queue.iter() {
+            self.l1_message_deadlines.entry(msg.id).or_insert(now);
+        }
+        let deadlines = &self.l1_message_deadlines;
+        self.l1_message_queue
+            .make_contiguous()
+            .sort_by_key(|e| deadlines[&e.id]);
+
+        self.l1_message_queue.len()
+    }
+
+    /// Returns the number of messages in `l1_message_queue` whose inclusion deadline
+    /// is at or before `now`. `SharedState::mine` doesn't mine pool transactions
+    /// while there are any.
+    pub fn overdue_l1_messages(&self, now: u64) -> usize {
+        self.l1_message_queue
+            .iter()
+            .filter(|e| self.l1_message_deadlines.get(&e.id) <= Some(&now))
+            .count()
+    }
+
+    /// Removes the messages `ids` from `l1_message_queue` once they were delivered or dropped.
+    pub fn remove_l1_messages(&mut self, ids: &[H256]) {
+        self.l1_message_queue.retain(|e| !ids.contains(&e.id));
+        for id in ids.iter() {
+            self.l1_message_deadlines.remove(id);
+        }
+    }
+
+    /// Forgets the proof requests of blocks up to and including `finalized_block`,
+    /// these don't complete through `request_proof` if the block was finalized otherwise.
+    pub fn prune_proof_requests(&mut self, finalized_block: U64) {
+        self.proof_requested_at
+            .retain(|block_num, _| *block_num > finalized_block);
+    }
+
+    /// Appends `dropped` to `dropped_messages` and forgets the oldest entries.
+    /// Appends `ids` to `l1_delivered_messages`, keeps the latest `DELIVERED_MESSAGES_HISTORY`.
+    pub fn record_l1_delivered_messages(&mut self, ids: &[H256]) {
+        self.l1_delivered_messages.extend_from_slice(ids);
+        truncate_front(&mut self.l1_delivered_messages, DELIVERED_MESSAGES_HISTORY);
+    }
+
+    /// Same as `record_l1_delivered_messages` for `l2_delivered_messages`.
+    pub fn record_l2_delivered_messages(&mut self, ids: &[H256]) {
+        self.l2_delivered_messages.extend_from_slice(ids);
+        truncate_front(&mut self.l2_delivered_messages, DELIVERED_MESSAGES_HISTORY);
+    }
+
+    pub fn record_dropped_messages(&mut self, dropped: impl IntoIterator<Item = DroppedMessage>) {
+        self.dropped_messages.extend(dropped);
+        while self.dropped_messages.len() > DROPPED_MESSAGES_HISTORY {
+            self.dropped_messages.pop_front();
+        }
+    }
+
+    /// Returns the status of the message `id` in either direction.
+    pub fn message_status(&self, id: H256) -> MessageStatusResponse {
+        let delivered =
+            self.l2_delivered_messages.contains(&id) || self.l1_delivered_messages.contains(&id);
+        let dropped = self.dropped_messages.iter().rev().find(|e| e.id == id);
+        let queued = self.l1_message_queue.iter().any(|e| e.id == id)
+            || self.l2_message_queue.iter().any(|e| e.id == id);
+
+        let status = if delivered {
+            MessageStatus::Delivered
+        } else if let Some(dropped) = dropped {
+            MessageStatus::Dropped {
+                reason: dropped.reason.clone(),
+                block_number: dropped.block_number,
+            }
+        } else if queued {
+            MessageStatus::Queued {
+                inclusion_deadline: self.l1_message_deadlines.get(&id).copied(),
+            }
+        } else {
+            MessageStatus::Unknown
+        };
+
+        MessageStatusResponse {
+            id,
+            status,
+            record: self.message_records.iter().find(|e| e.id == id).cloned(),
+        }
+    }
+
+    /// Returns the status of the queued messages of `direction`.
+    pub fn pending_messages(&self, direction: MessageDirection) -> Vec<MessageStatusResponse> {
+        let ids: Vec<H256> = match direction {
+            MessageDirection::L1ToL2 => self.l1_message_queue.iter().map(|e| e.id).collect(),
+            MessageDirection::L2ToL1 => self.l2_message_queue.iter().map(|e| e.id).collect(),
+        };
+
+        ids.into_iter().map(|id| self.message_status(id)).collect()
+    }
+}
+
+/// The maximum number of entries in `RwState::l1_blocks`.
+const L1_BLOCK_HISTORY: usize = 256;
+/// The maximum number of entries in `RwState::dropped_messages`.
+const DROPPED_MESSAGES_HISTORY: usize = 1024;
+/// The maximum number of entries in `RwState::message_records`.
+const MESSAGE_RECORDS_HISTORY: usize = 4096;
+/// The maximum number of entries in `RwState::l1_delivered_messages`
+/// and `RwState::l2_delivered_messages`.
+pub const DELIVERED_MESSAGES_HISTORY: usize = 4096;
+
+/// Removes the oldest entries of `list` until it has at most `max` entries.
+fn truncate_front<T>(list: &mut Vec<T>, max: usize) {
+    if list.len() > max {
+        list.drain(..list.len() - max);
+    }
+}
+
 #[derive(Clone)]
 pub struct SharedState {
     pub config: Arc<Mutex<Config>>,
@@ -142,11 +496,27 @@ impl SharedState {
         Self::new(&config).await
     }
 
+    /// Restores the state of a previous run if `Config::data_dir` is set.
+    /// Otherwise starts from the L2 genesis block and initializes the L1 bridge if necessary.
     pub async fn init(&self) {
         if !self.rw.lock().await.chain_state.head_block_hash.is_zero() {
             panic!("init");
         }
 
+        if let Some(state_store) = &self.ro.state_store {
+            if let Some(snapshot) = state_store.load().expect("load state") {
+                log::info!(
+                    "init with restored state: l1_last_sync_block={} l2_last_sync_block={}",
+                    snapshot.l1_last_sync_block,
+                    snapshot.l2_last_sync_block
+                );
+                let mut rw = self.rw.lock().await;
+                rw.restore(snapshot);
+                self.update_queue_metrics(&rw);
+                return;
+            }
+        }
+
         let genesis: Block<H256> = self
             .request_l2("eth_getBlockByNumber", ("0x0", false))
             .await
@@ -177,15 +547,19 @@ impl SharedState {
         }
     }
 
-    pub async fn sync(&self) {
+    /// Processes the events of the L1 bridge and L2 message deliveries.
+    /// The changes of each fetched block range are applied together with the sync progress,
+    /// therefore this step can be retried without processing events twice.
+    /// L1 reorgs are rolled back before, see `check_l1_reorg`.
+    pub async fn sync(&self) -> Result<(), CoordinatorError> {
+        self.check_l1_reorg().await?;
+
         // sync events
-        let latest_block: U64 = self
-            .request_l1("eth_blockNumber", ())
-            .await
-            .expect("eth_blockNumber");
-        let mut last_to_block: U64 = U64::zero();
+        let latest_block: U64 = self.request_l1("eth_blockNumber", ()).await?;
         let mut from: U64 = self.rw.lock().await.l1_last_sync_block + 1;
-        let mut filter = Filter::new()
+        let l1_rpc_url = self.config.lock().await.l1_rpc_url.clone();
+        let inclusion_timeout = self.config.lock().await.message_inclusion_timeout;
+        let filter = Filter::new()
             .address(ValueOrArray::Value(self.config.lock().await.l1_bridge))
             .topic0(ValueOrArray::Array(vec![
                 self.ro.block_beacon_topic,
@@ -194,47 +568,79 @@ impl SharedState {
                 self.ro.message_delivered_topic,
             ]));
 
-        while from <= latest_block {
-            // TODO: increase or decrease request range depending on fetch success
-            let to = cmp::min(from + 1u64, latest_block);
-            log::trace!("fetching l1 logs from={} to={}", from, to);
-            filter = filter.from_block(from).to_block(to);
-
-            let logs: Vec<Log> = self
-                .request_l1("eth_getLogs", [&filter])
-                .await
-                .expect("eth_getLogs");
-            // TODO: ugly hack to fix geth inconstency issues
-            if !logs.is_empty() {
-                last_to_block = to;
-            }
-
-            for log in logs {
+        while let Some(range) = self
+            .ro
+            .l1_log_fetcher
+            .next(
+                &self.ro.http_client,
+                &l1_rpc_url,
+                &filter,
+                from,
+                latest_block,
+            )
+            .await?
+        {
+            let start_state = self.rw.lock().await.chain_state;
+            let mut chain_state = start_state;
+            let mut records: Vec<L1BlockRecord> = Vec::new();
+            let mut submitted_blocks: HashMap<H256, VecDeque<H256>> = HashMap::new();
+            let mut l1_messages = Vec::new();
+            let mut l1_delivered_messages = Vec::new();
+            let mut l2_messages = Vec::new();
+            let mut dispatches: Vec<(H256, MessageDirection, Option<H256>)> = Vec::new();
+            let mut deliveries: Vec<Option<H256>> = Vec::new();
+            let mut submitted: Vec<H256> = Vec::new();
+            let mut finalized: Vec<H256> = Vec::new();
+
+            for log in range.logs {
+                if log.removed == Some(true) {
+                    continue;
+                }
+                let (number, hash) = match (log.block_number, log.block_hash) {
+                    (Some(number), Some(hash)) => (number, hash),
+                    _ => {
+                        return Err(CoordinatorError::Invariant(
+                            "L1 log without block".to_string(),
+                        ))
+                    }
+                };
+                if records.last().map(|e| e.number) != Some(number) {
+                    records.push(L1BlockRecord::new(number, hash));
+                }
+                let record = records.last_mut().unwrap();
                 let topic = log.topics[0];
 
                 if topic == self.ro.block_beacon_topic {
-                    let tx_hash = log.transaction_hash.expect("log txhash");
-                    let tx: Transaction = self
-                        .request_l1("eth_getTransactionByHash", [tx_hash])
-                        .await
-                        .expect("tx");
-
-                    let tx_data = tx.input.as_ref();
-
-                    // TODO: handle the case if len < 68
-                    let len = U256::from(&tx_data[36..68]).as_usize();
-                    let start = 68;
-                    let end = start + len;
-                    if end > tx_data.len() {
-                        log::warn!("TODO: zeropad block data");
+                    let tx_hash = log.transaction_hash.ok_or_else(|| {
+                        CoordinatorError::Invariant("BlockSubmitted: no tx hash".to_string())
+                    })?;
+                    if !submitted_blocks.contains_key(&tx_hash) {
+                        let tx: Transaction = self
+                            .request_l1("eth_getTransactionByHash", [tx_hash])
+                            .await?;
+                        // e.g. submitted through another contract, the events are skipped
+                        let block_hashes =
+                            submitted_block_hashes(&self.ro.bridge_abi, tx.input.as_ref())
+                                .unwrap_or_else(|err| {
+                                    log::error!("BlockSubmitted: {:?} skipped: {}", tx_hash, err);
+                                    Vec::new()
+                                });
+                        submitted_blocks.insert(tx_hash, block_hashes.into());
                     }
-                    let rlp = rlp::Rlp::new(&tx_data[start..end]);
-                    let info = rlp.payload_info().expect("payload_info");
-                    let block_header = &rlp.as_raw()[0..info.header_len + info.value_len];
-                    let block_hash = H256::from(keccak256(block_header));
+                    // one event per block in the order of submission
+                    let block_hash = match submitted_blocks
+                        .get_mut(&tx_hash)
+                        .and_then(|e| e.pop_front())
+                    {
+                        Some(block_hash) => block_hash,
+                        None => {
+                            log::warn!("BlockSubmitted: no block for event in {:?}", tx_hash);
+                            continue;
+                        }
+                    };
                     log::info!("BlockSubmitted: {:?} via {:?}", block_hash, tx_hash);
 
-                    let resp: Result<serde_json::Value, String> =
+                    let resp: Result<serde_json::Value, JsonRpcRequestError> =
                         self.request_l2("eth_getHeaderByHash", [block_hash]).await;
 
                     if resp.is_err() {
@@ -245,7 +651,11 @@ impl SharedState {
                         );
                     }
 
-                    self.rw.lock().await.chain_state.safe_block_hash = block_hash;
+                    record
+                        .prev_safe_block_hash
+                        .get_or_insert(chain_state.safe_block_hash);
+                    chain_state.safe_block_hash = block_hash;
+                    submitted.push(block_hash);
                     continue;
                 }
 
@@ -257,42 +667,158 @@ impl SharedState {
                         log.transaction_hash
                     );
 
-                    self.rw.lock().await.chain_state.finalized_block_hash = block_hash;
-                    self.record_l2_messages(block_hash).await;
+                    record
+                        .prev_finalized_block_hash
+                        .get_or_insert(chain_state.finalized_block_hash);
+                    chain_state.finalized_block_hash = block_hash;
+                    finalized.push(block_hash);
+                    for (beacon, tx_hash) in self.fetch_l2_messages(block_hash).await? {
+                        record.l2_messages.push(beacon.id);
+                        dispatches.push((beacon.id, MessageDirection::L2ToL1, tx_hash));
+                        l2_messages.push(beacon);
+                    }
                     continue;
                 }
 
                 if topic == self.ro.message_dispatched_topic {
+                    let tx_hash = log.transaction_hash;
                     let beacon = self._parse_message_beacon(log);
                     log::info!("L1:MessageDispatched:{:?}", beacon.id);
                     log::debug!("{:?}", beacon);
-                    self.rw.lock().await.l1_message_queue.push_back(beacon);
+                    record.l1_messages.push(beacon.id);
+                    dispatches.push((beacon.id, MessageDirection::L1ToL2, tx_hash));
+                    l1_messages.push(beacon);
                     continue;
                 }
 
                 if topic == self.ro.message_delivered_topic {
                     let id = H256::from_slice(log.data.as_ref());
                     log::info!("L1:MessageDelivered:{:?}", id);
-                    self.rw.lock().await.l1_delivered_messages.push(id);
+                    record.l1_delivered_messages.push(id);
+                    l1_delivered_messages.push(id);
+                    deliveries.push(log.transaction_hash);
                     continue;
                 }
             }
 
-            from = to + 1u64;
+            // also track the last synced block to detect reorgs of blocks without events
+            if range.synced >= range.from && records.last().map(|e| e.number) != Some(range.synced)
+            {
+                let header: BlockHeader = self
+                    .request_l1("eth_getHeaderByNumber", [range.synced])
+                    .await?;
+                records.push(L1BlockRecord::new(range.synced, header.hash));
+            }
+
+            {
+                let mut rw = self.rw.lock().await;
+                // the other stages run concurrently, only apply the hashes this range changed
+                // and only if they are still the ones this range started with
+                let safe_changed = !submitted.is_empty();
+                let finalized_changed = !finalized.is_empty();
+                if (safe_changed && rw.chain_state.safe_block_hash != start_state.safe_block_hash)
+                    || (finalized_changed
+                        && rw.chain_state.finalized_block_hash != start_state.finalized_block_hash)
+                {
+                    return Err(CoordinatorError::Invariant(
+                        "chain state changed during sync".to_string(),
+                    ));
+                }
+                if safe_changed {
+                    rw.chain_state.safe_block_hash = chain_state.safe_block_hash;
+                }
+                if finalized_changed {
+                    rw.chain_state.finalized_block_hash = chain_state.finalized_block_hash;
+                }
+                for block_hash in submitted.iter() {
+                    rw.pending_submissions.remove(block_hash);
+                }
+                for block_hash in finalized.iter() {
+                    rw.pending_finalizations.remove(block_hash);
+                }
+                let now = timestamp();
+                for (id, direction, tx_hash) in dispatches {
+                    rw.record_message_dispatch(id, direction, tx_hash, now);
+                }
+                for (id, tx_hash) in l1_delivered_messages.iter().zip(deliveries) {
+                    rw.record_message_delivery(*id, MessageDirection::L2ToL1, tx_hash, now);
+                }
+                let inclusion_deadline = now + inclusion_timeout;
+                for msg in l1_messages.iter() {
+                    rw.l1_message_deadlines.insert(msg.id, inclusion_deadline);
+                }
+                rw.l1_message_queue.extend(l1_messages);
+                rw.record_l1_delivered_messages(&l1_delivered_messages);
+                rw.l2_message_queue.extend(l2_messages);
+                rw.l1_blocks.extend(records);
+                while rw.l1_blocks.len() > L1_BLOCK_HISTORY {
+                    rw.l1_blocks.pop_front();
+                }
+                rw.l1_last_sync_block = range.synced;
+                self.update_queue_metrics(&rw);
+            }
+
+            from = range.to + 1u64;
         }
 
-        if last_to_block != U64::zero() {
-            self.rw.lock().await.l1_last_sync_block = last_to_block;
+        self.sync_l2().await
+    }
+
+    /// Compares the processed L1 blocks with the canonical chain and
+    /// rolls back the events of blocks that are not part of it anymore.
+    /// The canonical events are applied by the next sync, which starts at the common ancestor.
+    async fn check_l1_reorg(&self) -> Result<(), CoordinatorError> {
+        let processed: Vec<(U64, H256)> = self
+            .rw
+            .lock()
+            .await
+            .l1_blocks
+            .iter()
+            .rev()
+            .map(|e| (e.number, e.hash))
+            .collect();
+        let mut ancestor = None;
+        let mut reorged = false;
+        for (number, hash) in processed {
+            let canonical: Result<BlockHeader, JsonRpcRequestError> =
+                self.request_l1("eth_getHeaderByNumber", [number]).await;
+            let canonical_hash = match canonical {
+                Ok(header) => Some(header.hash),
+                // the chain is shorter now
+                Err(JsonRpcRequestError::NoResult) => None,
+                Err(err) => return Err(err.into()),
+            };
+            if canonical_hash == Some(hash) {
+                ancestor = Some(number);
+                break;
+            }
+            reorged = true;
+        }
+
+        if !reorged {
+            return Ok(());
         }
-        self.sync_l2().await;
+        let ancestor = ancestor.ok_or_else(|| {
+            CoordinatorError::Invariant("L1 reorg is deeper than the processed blocks".to_string())
+        })?;
+        log::warn!("L1 reorg detected, rolling back to block {}", ancestor);
+
+        let mut rw = self.rw.lock().await;
+        rw.rollback_l1(ancestor);
+        self.update_queue_metrics(&rw);
+
+        Ok(())
     }
 
-    pub async fn mine(&self) {
+    /// Delivers pending L1 > L2 messages and mines a block of pending transactions
+    /// according to `block_interval`, `min_block_txs` and `empty_block_interval`.
+    /// Messages are only removed from `l1_message_queue` once the block was built.
+    pub async fn mine(&self) -> Result<(), CoordinatorError> {
         // TODO: verify that head_hash is correct
-        let head_hash = get_chain_head(&self.ro.http_client, &self.config.lock().await.l2_rpc_url)
-            .await
-            .hash;
-        self.rw.lock().await.chain_state.head_block_hash = head_hash;
+        let head =
+            get_chain_head(&self.ro.http_client, &self.config.lock().await.l2_rpc_url).await?;
+        self.rw.lock().await.chain_state.head_block_hash = head.hash;
+        let mut head_timestamp = head.timestamp.as_u64();
 
         {
             // always send a miner_init request to enable transaction pool etc.
@@ -302,29 +828,25 @@ impl SharedState {
 
         {
             // check l1 > l2 message queue
-            let len = self.rw.lock().await.l1_message_queue.len();
+            let len = self.rw.lock().await.order_l1_message_queue(timestamp());
             if len > 0 {
                 let mut nonce: U256 = self
                     .request_l2(
                         "eth_getTransactionCount",
                         (self.ro.l2_wallet.address(), "latest"),
                     )
-                    .await
-                    .expect("nonce");
+                    .await?;
 
                 const LOG_TAG: &str = "L2:deliverMessage:";
 
                 // anchors a L1 block into L2
-                let l1_block_header: BlockHeader = self
-                    .request_l1("eth_getHeaderByNumber", ["latest"])
-                    .await
-                    .expect("l1 block header");
+                let l1_block_header: BlockHeader =
+                    self.request_l1("eth_getHeaderByNumber", ["latest"]).await?;
                 // TODO: figure out how to get by hash - gonna be safer
                 // Or just hash it and compare against l1_block_header.hash.
                 let block_data: Bytes = self
                     .request_l1("debug_getHeaderRlp", [l1_block_header.number.as_u64()])
-                    .await
-                    .expect("block_data");
+                    .await?;
                 // import l1 block
                 let calldata = self
                     .ro
@@ -338,42 +860,53 @@ impl SharedState {
                     ])
                     .expect("calldata");
                 let block_import_tx = self
-                    .sign_l2(
+                    .sign_l2_given_block_tag(
                         self.ro.l2_message_deliverer_addr,
                         U256::zero(),
                         nonce,
                         calldata,
+                        None,
                     )
-                    .await;
+                    .await?;
                 nonce = nonce + 1;
 
                 // Use this block to run the messages against.
                 // This is required for proper gas calculation.
                 let mut messages = vec![block_import_tx];
                 let block_timestamp = self.next_timestamp().await;
-                let mut temporary_block = self
-                    .prepare_block(block_timestamp, Some(&messages))
-                    .await
-                    .expect("prepare block with import tx");
+                let mut temporary_block =
+                    self.prepare_block(block_timestamp, Some(&messages)).await?;
 
                 let ts = U256::from(block_timestamp);
-                let mut drop_idxs = Vec::new();
-                let mut i = 0;
+                let mut drop_ids = Vec::new();
+                let mut dropped = Vec::new();
                 let l1_bridge_addr = self.config.lock().await.l1_bridge;
-                loop {
-                    let rw = self.rw.lock().await;
-                    let msg = rw.l1_message_queue.get(i);
-                    if msg.is_none() {
-                        break;
-                    }
-                    let msg = msg.unwrap().clone();
-                    drop(rw);
+                // `sync` changes the queue concurrently,
+                // work on a copy and remove the processed messages by id
+                let queue: Vec<MessageBeacon> = self
+                    .rw
+                    .lock()
+                    .await
+                    .l1_message_queue
+                    .iter()
+                    .cloned()
+                    .collect();
+                for msg in queue {
+                    let block_number = temporary_block.number.unwrap();
+                    let mut drop_msg = |reason| {
+                        dropped.push(DroppedMessage {
+                            id: msg.id,
+                            reason,
+                            block_number,
+                            timestamp: block_timestamp,
+                        })
+                    };
 
                     if msg.deadline < ts {
                         log::info!("{} {:?} deadline exceeded", LOG_TAG, msg.id);
                         log::debug!("{:?}", msg);
-                        drop_idxs.push(i);
-                        i += 1;
+                        drop_msg(DropReason::DeadlineExceeded);
+                        drop_ids.push(msg.id);
                         continue;
                     }
 
@@ -390,8 +923,7 @@ impl SharedState {
                         log::debug!("{:?}", msg);
 
                         if found {
-                            drop_idxs.push(i);
-                            i += 1;
+                            drop_ids.push(msg.id);
                             continue;
                         }
                     }
@@ -404,8 +936,7 @@ impl SharedState {
                             "eth_getProof",
                             (l1_bridge_addr, [storage_slot], l1_block_header.hash),
                         )
-                        .await
-                        .expect("eth_getProof");
+                        .await?;
                     // encode proof
                     let proof: Bytes = Bytes::from(marshal_proof(
                         &proof_obj.account_proof,
@@ -438,32 +969,41 @@ impl SharedState {
                             Some(format!("{:#066x}", temporary_block.hash.unwrap())),
                         )
                         .await;
-                    if let Err(err) = tx {
-                        log::debug!("{} simulate tx {}", LOG_TAG, err);
-                        drop_idxs.push(i);
-                        i += 1;
-                        continue;
-                    }
+                    let tx = match tx {
+                        // the node may be unavailable, the message is simulated again
+                        Err(err) if err.is_transient() => return Err(err),
+                        Err(err) => {
+                            log::debug!("{} simulate tx {}", LOG_TAG, err);
+                            drop_msg(DropReason::SimulationFailed(err.to_string()));
+                            drop_ids.push(msg.id);
+                            continue;
+                        }
+                        Ok(tx) => tx,
+                    };
 
                     // try to build that block
-                    messages.push(tx.unwrap());
-                    let tmp = self.prepare_block(block_timestamp, Some(&messages)).await;
+                    messages.push(tx);
+                    let mut tmp = self.prepare_block(block_timestamp, Some(&messages)).await;
+                    if let Ok(block) = &tmp {
+                        if !self.is_provable(block).await? {
+                            // the block is full for the prover
+                            tmp = Err("gas limit reached".to_string());
+                        }
+                    }
                     if let Err(err) = tmp {
                         log::debug!("{} {}", LOG_TAG, err);
                         // bad tx
                         messages.pop();
 
-                        match err.as_str() {
-                            "gas limit reached" => {
-                                // block is full
-                                break;
-                            }
-                            _ => {
-                                // another error, probably a revert
-                                drop_idxs.push(i);
-                                i += 1;
+                        // the first message if only the block import transaction is left
+                        match DropReason::from_block_error(err, messages.len() == 1) {
+                            Some(reason) => {
+                                drop_msg(reason);
+                                drop_ids.push(msg.id);
                                 continue;
                             }
+                            // block is full
+                            None => break,
                         }
                     }
 
@@ -476,39 +1016,83 @@ impl SharedState {
                         temporary_block.gas_limit
                     );
                     nonce = nonce + 1;
-                    drop_idxs.push(i);
-                    i += 1;
+                    drop_ids.push(msg.id);
                 }
 
                 // final step
                 if temporary_block.transactions.len() > 1 {
-                    self.set_chain_head(temporary_block.hash.unwrap())
-                        .await
-                        .expect("set_chain_head relay");
+                    self.set_chain_head(temporary_block.hash.unwrap()).await?;
+                    head_timestamp = block_timestamp;
                 }
 
                 // everything went well
                 let mut rw = self.rw.lock().await;
-                for (i, original_pos) in drop_idxs.into_iter().enumerate() {
-                    rw.l1_message_queue.remove(original_pos - i);
-                }
+                rw.remove_l1_messages(&drop_ids);
+                rw.record_dropped_messages(dropped);
+                self.update_queue_metrics(&rw);
+            }
+        }
+
+        {
+            // forced inclusion, overdue messages go before the transactions of the pool
+            let overdue = self.rw.lock().await.overdue_l1_messages(timestamp());
+            if overdue > 0 {
+                log::info!("{} overdue messages, not mining pool transactions", overdue);
+                return Ok(());
             }
         }
 
         // check if we can mine a block
-        let resp: TxpoolStatus = self.request_l2("txpool_status", ()).await.unwrap();
+        let resp: TxpoolStatus = self.request_l2("txpool_status", ()).await?;
         let pending_txs = resp.pending.as_u64();
+        let elapsed = timestamp().saturating_sub(head_timestamp);
+        let (seal, heartbeat) = {
+            let config = self.config.lock().await;
+            (
+                config.seal_block(elapsed, pending_txs),
+                config.heartbeat_due(elapsed),
+            )
+        };
 
-        if pending_txs != 0 {
-            self.mine_block(None).await.expect("mine_block regular");
+        if seal {
+            self.mine_pool_block(heartbeat).await?;
         }
+
+        Ok(())
     }
 
-    pub async fn submit_blocks(&self) {
+    /// Seals a block from the transaction pool and sets the chain head,
+    /// see `prepare_provable_block`. If all pool transactions were skipped,
+    /// only seals an empty block if `heartbeat` is set.
+    async fn mine_pool_block(&self, heartbeat: bool) -> Result<(), CoordinatorError> {
+        let block_timestamp = self.next_timestamp().await;
+        let block = match self.prepare_provable_block(block_timestamp).await? {
+            Some(block) => block,
+            None if heartbeat => self.prepare_block(block_timestamp, Some(&vec![])).await?,
+            None => {
+                log::info!("no provable transactions in the pool");
+                return Ok(());
+            }
+        };
+        self.set_chain_head(block.hash.unwrap()).await?;
+
+        Ok(())
+    }
+
+    /// Submits all blocks between `safe_block_hash` and `head_block_hash` to L1.
+    /// `safe_block_hash` is advanced by `sync` once it sees the submissions,
+    /// blocks in `pending_submissions` are not submitted again until then.
+    pub async fn submit_blocks(&self) -> Result<(), CoordinatorError> {
         // block submission
         let safe_hash = self.rw.lock().await.chain_state.safe_block_hash;
         let head_hash = self.rw.lock().await.chain_state.head_block_hash;
-        if safe_hash != head_hash {
+        if safe_hash == head_hash {
+            self.ro
+                .metrics
+                .block_gap
+                .with_label_values(&["head_safe"])
+                .set(0.0);
+        } else {
             // find all the blocks since `safe_hash`
             let blocks = get_blocks_between(
                 &self.ro.http_client,
@@ -516,17 +1100,54 @@ impl SharedState {
                 &safe_hash,
                 &head_hash,
             )
-            .await;
+            .await?;
             let l1_bridge_addr = Some(self.config.lock().await.l1_bridge);
+            self.ro
+                .metrics
+                .block_gap
+                .with_label_values(&["head_safe"])
+                .set(blocks.len() as f64);
+            let blocks: Vec<Block<H256>> = {
+                let rw = self.rw.lock().await;
+                blocks
+                    .into_iter()
+                    .filter(|block| !rw.pending_submissions.contains(&block.hash.unwrap()))
+                    .collect()
+            };
 
             log::trace!("blocks to be submitted: {:?}", blocks.len());
+            let config = self.config.lock().await;
+            let submit_batch = config.submit_batch;
+            let calldata_limit = config.submit_batch_calldata_limit;
+            let gas_limit = config.submit_batch_gas_limit;
+            drop(config);
+
+            if submit_batch {
+                let mut batch: Vec<(&Block<H256>, Bytes)> = Vec::new();
+                let mut batch_size = 0;
+                for block in blocks.iter().rev() {
+                    let witness = self.request_witness(&block.number.unwrap()).await?.input;
+                    // offset, length and padded data inside the `bytes[]` encoding
+                    let size = 64 + (witness.len() + 31) / 32 * 32;
+                    if !batch.is_empty() && batch_size + size > calldata_limit {
+                        self.submit_batch(&batch, gas_limit).await?;
+                        batch.clear();
+                        batch_size = 0;
+                    }
+                    batch.push((block, witness));
+                    batch_size += size;
+                }
+                if !batch.is_empty() {
+                    self.submit_batch(&batch, gas_limit).await?;
+                }
+
+                return Ok(());
+            }
+
             for block in blocks.iter().rev() {
                 log::info!("submit_block: {}", format_block(block));
                 {
-                    let witness = self
-                        .request_witness(&block.number.unwrap())
-                        .await
-                        .expect("witness");
+                    let witness = self.request_witness(&block.number.unwrap()).await?;
                     let block_data = witness.input;
                     let calldata = self
                         .ro
@@ -536,37 +1157,168 @@ impl SharedState {
                         .encode_input(&[block_data.into_token()])
                         .expect("calldata");
 
-                    self.transaction_to_l1(l1_bridge_addr, U256::zero(), calldata)
+                    let receipt = self
+                        .transaction_to_l1(l1_bridge_addr, U256::zero(), calldata)
+                        .await?;
+                    self.observe_l1_gas("submitBlock", &receipt);
+                    self.observe_l1_gas_per_block(&receipt, 1);
+                    // a retry of this step must not submit the block again
+                    self.rw
+                        .lock()
                         .await
-                        .expect("receipt");
+                        .pending_submissions
+                        .insert(block.hash.unwrap());
                 }
             }
         }
+
+        Ok(())
     }
 
-    pub async fn finalize_blocks(&self) -> Result<(), String> {
+    /// Submits the blocks and witnesses of `batch` in order with `submitBlocks`.
+    /// The batch is split into multiple transactions if the estimated gas exceeds `gas_limit`.
+    async fn submit_batch(
+        &self,
+        batch: &[(&Block<H256>, Bytes)],
+        gas_limit: u64,
+    ) -> Result<(), CoordinatorError> {
+        let l1_bridge_addr = self.config.lock().await.l1_bridge;
+        let function = self.ro.bridge_abi.function("submitBlocks").unwrap();
+        let mut start = 0;
+
+        while start < batch.len() {
+            let mut end = batch.len();
+            let calldata = loop {
+                let witnesses = batch[start..end]
+                    .iter()
+                    .map(|(_, witness)| witness.clone().into_token())
+                    .collect();
+                let calldata = function
+                    .encode_input(&[Token::Array(witnesses)])
+                    .expect("calldata");
+                if end - start == 1 {
+                    break calldata;
+                }
+
+                let estimate: U256 = jsonrpc_call(
+                    RPC_REQUEST_TIMEOUT,
+                    &self.ro.http_client,
+                    &self.config.lock().await.l1_rpc_url,
+                    "eth_estimateGas",
+                    [serde_json::json!({
+                        "from": self.ro.l1_wallet.address(),
+                        "to": l1_bridge_addr,
+                        "data": Bytes::from(calldata.clone()),
+                    })],
+                )
+                .await?;
+                if estimate <= U256::from(gas_limit) {
+                    break calldata;
+                }
+                end = start + (end - start) / 2;
+            };
+
+            log::info!(
+                "submit_blocks: {} blocks from {} to {}",
+                end - start,
+                format_block(batch[start].0),
+                format_block(batch[end - 1].0)
+            );
+            let receipt = self
+                .transaction_to_l1(Some(l1_bridge_addr), U256::zero(), calldata)
+                .await?;
+            self.observe_l1_gas("submitBlocks", &receipt);
+            self.observe_l1_gas_per_block(&receipt, end - start);
+            // see `submit_blocks`
+            self.rw.lock().await.pending_submissions.extend(
+                batch[start..end]
+                    .iter()
+                    .map(|(block, _)| block.hash.unwrap()),
+            );
+            start = end;
+        }
+
+        Ok(())
+    }
+
+    pub async fn finalize_blocks(&self) -> Result<(), CoordinatorError> {
         // block finalization
         let safe_hash = self.rw.lock().await.chain_state.safe_block_hash;
         let final_hash = self.rw.lock().await.chain_state.finalized_block_hash;
-        if final_hash != safe_hash {
+        if final_hash == safe_hash {
+            self.ro
+                .metrics
+                .block_gap
+                .with_label_values(&["safe_finalized"])
+                .set(0.0);
+        } else {
             let blocks = get_blocks_between(
                 &self.ro.http_client,
                 &self.config.lock().await.l2_rpc_url,
                 &final_hash,
                 &safe_hash,
             )
-            .await;
+            .await?;
+            self.ro
+                .metrics
+                .block_gap
+                .with_label_values(&["safe_finalized"])
+                .set(blocks.len() as f64);
+
+            // the parent of the oldest block is the finalized block
+            if let Some(block_num) = blocks.last().and_then(|block| block.number) {
+                self.rw.lock().await.prune_proof_requests(block_num - 1u64);
+            }
 
             log::trace!("blocks for finalization: {:?}", blocks.len());
-            for block in blocks.iter().rev() {
-                self.finalize_block(block).await?;
+            // `set_config` bypasses the validation of the command line
+            let finalize_batch = self
+                .config
+                .lock()
+                .await
+                .finalize_batch
+                .min(MAX_AGGREGATION_BLOCKS);
+            let blocks: Vec<Block<H256>> = {
+                // finalized blocks that `sync` didn't see yet
+                let rw = self.rw.lock().await;
+                blocks
+                    .into_iter()
+                    .rev()
+                    .filter(|block| !rw.pending_finalizations.contains(&block.hash.unwrap()))
+                    .collect()
+            };
+            let mut i = 0;
+            while i < blocks.len() {
+                // only full batches to keep the proof requests stable,
+                // the remaining blocks are finalized one by one.
+                let end = i + finalize_batch;
+                if finalize_batch > 1 && end <= blocks.len() {
+                    match self.finalize_block_range(&blocks[i..end]).await {
+                        Ok(()) => {
+                            i = end;
+                            continue;
+                        }
+                        // the blocks may not be provable together,
+                        // e.g. because they need different circuit parameters,
+                        // or the bridge may reject the aggregated proof.
+                        Err(CoordinatorError::Rpc(err) | CoordinatorError::Revert(err)) => {
+                            log::warn!(
+                                "finalize_block_range: falling back to a single block: {}",
+                                err
+                            )
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+                self.finalize_block(&blocks[i]).await?;
+                i += 1;
             }
         }
 
         Ok(())
     }
 
-    pub async fn finalize_block(&self, block: &Block<H256>) -> Result<(), String> {
+    pub async fn finalize_block(&self, block: &Block<H256>) -> Result<(), CoordinatorError> {
         const LOG_TAG: &str = "L1:finalize_block:";
         log::trace!("{} {}", LOG_TAG, format_block(block));
 
@@ -576,7 +1328,7 @@ impl SharedState {
         if let Err(err) = proofs {
             log::error!("{}:{} {:?}", LOG_TAG, block_num, err);
 
-            return Err(err);
+            return Err(CoordinatorError::Rpc(err));
         }
 
         match proofs.unwrap() {
@@ -584,76 +1336,117 @@ impl SharedState {
             Some(proof) => {
                 log::info!("{} found proof: {:#?} for {}", LOG_TAG, proof, block_num);
 
-                // choose the aggregation proof if not empty
-                let (is_aggregated, proof_result) = {
-                    if proof.aggregation.proof.len() != 0 {
-                        (true, proof.aggregation)
-                    } else {
-                        (false, proof.circuit)
-                    }
-                };
-
-                let mut verifier_calldata = vec![];
-                let mut tmp_buf = vec![0u8; 32];
-
-                proof_result.instance.iter().for_each(|v| {
-                    v.to_big_endian(&mut tmp_buf);
-                    verifier_calldata.extend_from_slice(&tmp_buf);
-                });
-                verifier_calldata.extend_from_slice(proof_result.proof.as_ref());
+                let proof_data = finalize_proof_data(block.hash.unwrap(), proof);
+                log::debug!("proof_data: {}", proof_data);
+                let calldata = self
+                    .ro
+                    .bridge_abi
+                    .function("finalizeBlock")
+                    .unwrap()
+                    .encode_input(&[proof_data.into_token()])
+                    .expect("calldata");
 
-                let mut proof_data = vec![];
-                proof_data.extend_from_slice(block.hash.unwrap().as_ref());
+                let l1_bridge_addr = Some(self.config.lock().await.l1_bridge);
+                let receipt = self
+                    .transaction_to_l1(l1_bridge_addr, U256::zero(), calldata)
+                    .await?;
+                self.observe_l1_gas("finalizeBlock", &receipt);
+                // `sync` advances `finalized_block_hash` once it sees the `BlockFinalized` event,
+                // but a retry of this step must not finalize the block again
+                self.rw
+                    .lock()
+                    .await
+                    .pending_finalizations
+                    .insert(block.hash.unwrap());
+            }
+        }
 
-                // this is temporary until proper contract setup
-                let verifier_addr = U256::from(proof_result.label.as_bytes());
-                verifier_addr.to_big_endian(&mut tmp_buf);
-                proof_data.extend_from_slice(&tmp_buf);
+        Ok(())
+    }
 
-                let is_aggregated = match is_aggregated {
-                    true => U256::one(),
-                    false => U256::zero(),
-                };
-                is_aggregated.to_big_endian(&mut tmp_buf);
-                proof_data.extend_from_slice(&tmp_buf);
+    /// Finalizes the consecutive `blocks` with one proof for all blocks via `finalizeBlocks`.
+    /// `blocks` must be in ascending order.
+    pub async fn finalize_block_range(
+        &self,
+        blocks: &[Block<H256>],
+    ) -> Result<(), CoordinatorError> {
+        const LOG_TAG: &str = "L1:finalize_block_range:";
+        let first = blocks.first().expect("blocks");
+        let last = blocks.last().expect("blocks");
+        let from = first.number.unwrap();
+        let to = last.number.unwrap();
+        log::trace!("{} {}..={}", LOG_TAG, from, to);
+
+        let proofs = self.request_proof_range(&from, &to).await;
+        if let Err(err) = proofs {
+            log::error!("{}:{}..={} {:?}", LOG_TAG, from, to, err);
 
-                proof_data.extend_from_slice(&verifier_calldata);
+            return Err(CoordinatorError::Rpc(err));
+        }
 
-                let proof_data = Bytes::from(proof_data);
+        match proofs.unwrap() {
+            None => log::trace!("{} proof not yet computed for: {}..={}", LOG_TAG, from, to),
+            Some(proof) => {
+                log::info!(
+                    "{} found proof: {:#?} for {}..={}",
+                    LOG_TAG,
+                    proof,
+                    from,
+                    to
+                );
+
+                let proof_data = finalize_proof_data(last.hash.unwrap(), proof);
                 log::debug!("proof_data: {}", proof_data);
+                let block_hashes = blocks
+                    .iter()
+                    .map(|block| block.hash.unwrap().into_token())
+                    .collect();
                 let calldata = self
                     .ro
                     .bridge_abi
-                    .function("finalizeBlock")
+                    .function("finalizeBlocks")
                     .unwrap()
-                    .encode_input(&[proof_data.into_token()])
+                    .encode_input(&[Token::Array(block_hashes), proof_data.into_token()])
                     .expect("calldata");
 
                 let l1_bridge_addr = Some(self.config.lock().await.l1_bridge);
-                self.transaction_to_l1(l1_bridge_addr, U256::zero(), calldata)
+                let receipt = self
+                    .transaction_to_l1(l1_bridge_addr, U256::zero(), calldata)
+                    .await?;
+                self.observe_l1_gas("finalizeBlocks", &receipt);
+                // see `finalize_block`
+                self.rw
+                    .lock()
                     .await
-                    .expect("receipt");
+                    .pending_finalizations
+                    .extend(blocks.iter().map(|block| block.hash.unwrap()));
             }
         }
 
         Ok(())
     }
 
+    /// Sends a transaction from `l1_wallet`.
     pub async fn transaction_to_l1(
         &self,
         to: Option<Address>,
         value: U256,
         calldata: Vec<u8>,
-    ) -> Result<TransactionReceipt, String> {
-        send_transaction_to_l1(
-            &self.ro.http_client,
-            &self.config.lock().await.l1_rpc_url,
-            &self.ro.l1_wallet,
-            to,
-            value,
-            calldata,
-        )
-        .await
+    ) -> Result<TransactionReceipt, TxError> {
+        self.transaction_to_l1_from(&self.ro.l1_tx_manager, to, value, calldata)
+            .await
+    }
+
+    /// Sends a transaction from the wallet of `tx_manager`.
+    pub async fn transaction_to_l1_from(
+        &self,
+        tx_manager: &TxManager,
+        to: Option<Address>,
+        value: U256,
+        calldata: Vec<u8>,
+    ) -> Result<TransactionReceipt, TxError> {
+        let l1_rpc_url = self.config.lock().await.l1_rpc_url.clone();
+        tx_manager.send(&l1_rpc_url, to, value, calldata).await
     }
 
     pub async fn transaction_to_l2(
@@ -692,7 +1485,7 @@ impl SharedState {
         nonce: U256,
         calldata: Vec<u8>,
         option_block: Option<String>,
-    ) -> Result<Bytes, String> {
+    ) -> Result<Bytes, CoordinatorError> {
         let wallet = &self.ro.l2_wallet;
         let wallet_addr: Address = wallet.address();
         let gas_price: U256 = self.request_l2("eth_gasPrice", ()).await?;
@@ -707,20 +1500,17 @@ impl SharedState {
         let block_tag = option_block.unwrap_or_else(|| "latest".into());
         let estimate: U256 = self.request_l2("eth_estimateGas", (&tx, block_tag)).await?;
         let tx = tx.gas(estimate).into();
-        let sig = wallet
-            .sign_transaction(&tx)
-            .await
-            .map_err(|e| e.to_string())?;
+        let raw_tx = wallet.sign_transaction(&tx).await?;
 
-        Ok(tx.rlp_signed(&sig))
+        Ok(raw_tx)
     }
 
     pub async fn request_l1<T: Serialize + Send + Sync, R: DeserializeOwned>(
         &self,
         method: &str,
         args: T,
-    ) -> Result<R, String> {
-        jsonrpc_request_client(
+    ) -> Result<R, JsonRpcRequestError> {
+        jsonrpc_call(
             RPC_REQUEST_TIMEOUT,
             &self.ro.http_client,
             &self.config.lock().await.l1_rpc_url,
@@ -734,8 +1524,8 @@ impl SharedState {
         &self,
         method: &str,
         args: T,
-    ) -> Result<R, String> {
-        jsonrpc_request_client(
+    ) -> Result<R, JsonRpcRequestError> {
+        jsonrpc_call(
             RPC_REQUEST_TIMEOUT,
             &self.ro.http_client,
             &self.config.lock().await.l2_rpc_url,
@@ -789,6 +1579,108 @@ impl SharedState {
         Ok(prepared_block)
     }
 
+    /// Seals a block from the transaction pool and re-seals it with fewer transactions
+    /// until the prover can handle it, see `is_provable`.
+    /// A transaction that doesn't fit into a provable block on its own is skipped
+    /// together with the following transactions of its sender, see `skip_txs`.
+    /// Returns `None` if no transaction is left.
+    async fn prepare_provable_block(
+        &self,
+        timestamp: u64,
+    ) -> Result<Option<Block<Transaction>>, String> {
+        let (_, max_txs) = self.config.lock().await.block_limits();
+        let mut block = self.prepare_block(timestamp, None).await?;
+        let pool_txs = block.transactions.clone();
+        let mut txs = {
+            let mut rw = self.rw.lock().await;
+            // forget the transactions that left the pool
+            rw.unprovable_txs
+                .retain(|hash| pool_txs.iter().any(|tx| tx.hash == *hash));
+            skip_txs(&pool_txs, &rw.unprovable_txs)
+        };
+        let mut len = cmp::min(txs.len(), max_txs);
+        let mut reseal = len != pool_txs.len();
+
+        loop {
+            if len == 0 && !pool_txs.is_empty() {
+                return Ok(None);
+            }
+            if reseal {
+                let rlps: Vec<Bytes> = txs[..len].iter().map(|tx| tx.rlp()).collect();
+                block = self.prepare_block(timestamp, Some(&rlps)).await?;
+            }
+            if self.is_provable(&block).await? {
+                break;
+            }
+
+            if len == 1 {
+                log::warn!(
+                    "skipping transaction {:?}, it does not fit into a provable block",
+                    txs[0].hash
+                );
+                let mut rw = self.rw.lock().await;
+                rw.unprovable_txs.insert(txs[0].hash);
+                txs = skip_txs(&txs, &rw.unprovable_txs);
+                len = cmp::min(txs.len(), max_txs);
+            } else {
+                len -= 1;
+            }
+            reseal = true;
+        }
+
+        if len != pool_txs.len() {
+            log::info!(
+                "re-sealed block with {} of {} transactions",
+                len,
+                pool_txs.len()
+            );
+        }
+        Ok(Some(block))
+    }
+
+    /// Returns `true` if `block` is within `Config::block_limits` and
+    /// the circuits of the prover, see `check_circuit_limits`.
+    pub async fn is_provable(&self, block: &Block<Transaction>) -> Result<bool, String> {
+        let (max_gas, max_txs) = self.config.lock().await.block_limits();
+        let bytecode_len = self.block_bytecode_len(block).await?;
+
+        match check_circuit_limits(block, bytecode_len, max_gas, max_txs) {
+            Ok(()) => Ok(true),
+            Err(err) => {
+                log::info!("block {:?} is not provable: {}", block.hash.unwrap(), err);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Returns the size of the distinct bytecodes that `block` executes or deploys.
+    async fn block_bytecode_len(&self, block: &Block<Transaction>) -> Result<usize, String> {
+        if block.transactions.is_empty() {
+            return Ok(0);
+        }
+
+        let traces: Vec<PrestateTrace> = self
+            .request_l2(
+                "debug_traceBlockByHash",
+                (
+                    block.hash.unwrap(),
+                    serde_json::json!({ "tracer": "prestateTracer" }),
+                ),
+            )
+            .await?;
+        let mut codes: HashMap<H256, usize> = HashMap::new();
+        let accounts = traces.iter().flat_map(|trace| trace.result.values());
+        for code in accounts.map(|account| &account.code) {
+            codes.insert(keccak256(code).into(), code.len());
+        }
+        // init code of contract deployments
+        for tx in block.transactions.iter().filter(|tx| tx.to.is_none()) {
+            codes.insert(keccak256(&tx.input).into(), tx.input.len());
+        }
+
+        Ok(codes.values().sum())
+    }
+
     /// Set canonical chain head on `l2_node` and update `chain_state`.
     pub async fn set_chain_head(&self, block_hash: H256) -> Result<(), String> {
         let res: bool = self.request_l2("miner_setHead", [block_hash]).await?;
@@ -803,6 +1695,7 @@ impl SharedState {
 
     /// Mines a new block on `l2_node` and sets the chain head.
     /// The transaction pool will be used if `transactions` is `None`.
+    /// Fails without changing the chain head if the block is not provable.
     pub async fn mine_block(
         &self,
         transactions: Option<&Vec<Bytes>>,
@@ -811,164 +1704,225 @@ impl SharedState {
             .prepare_block(self.next_timestamp().await, transactions)
             .await?;
 
+        if !self.is_provable(&block).await? {
+            return Err(format!("block {:?} is not provable", block.hash.unwrap()));
+        }
+
         self.set_chain_head(block.hash.unwrap()).await?;
         Ok(block)
     }
 
     /// keeps track of l2 bridge message events
-    async fn sync_l2(&self) {
-        // TODO: DRY syncing mechanics w/ l1
-        let latest_block: U64 = self
-            .request_l2("eth_blockNumber", ())
-            .await
-            .expect("eth_blockNumber");
-        let mut last_to_block: U64 = U64::zero();
+    async fn sync_l2(&self) -> Result<(), CoordinatorError> {
+        let latest_block: U64 = self.request_l2("eth_blockNumber", ()).await?;
         let mut from: U64 = self.rw.lock().await.l2_last_sync_block + 1;
-        let mut filter = Filter::new()
+        let l2_rpc_url = self.config.lock().await.l2_rpc_url.clone();
+        let filter = Filter::new()
             .address(ValueOrArray::Value(self.ro.l2_message_deliverer_addr))
             .topic0(ValueOrArray::Value(self.ro.message_delivered_topic));
-        let mut executed_msgs = vec![];
 
-        while from <= latest_block {
-            // TODO: increase or decrease request range depending on fetch success
-            let to = cmp::min(from + 1u64, latest_block);
-            log::trace!("fetching logs from={} to={}", from, to);
-            filter = filter.from_block(from).to_block(to);
-
-            let logs: Vec<Log> = self
-                .request_l2("eth_getLogs", [&filter])
-                .await
-                .expect("eth_getLogs");
-            // TODO: ugly hack to fix geth inconstency issues
-            if !logs.is_empty() {
-                last_to_block = to;
-            }
+        while let Some(range) = self
+            .ro
+            .l2_log_fetcher
+            .next(
+                &self.ro.http_client,
+                &l2_rpc_url,
+                &filter,
+                from,
+                latest_block,
+            )
+            .await?
+        {
+            let executed_msgs: Vec<H256> = range
+                .logs
+                .iter()
+                .map(|log| H256::from_slice(log.data.as_ref()))
+                .collect();
 
-            for log in logs {
-                let message_id = H256::from_slice(log.data.as_ref());
-                executed_msgs.push(message_id);
+            {
+                let mut rw = self.rw.lock().await;
+                let now = timestamp();
+                for (id, log) in executed_msgs.iter().zip(range.logs.iter()) {
+                    rw.record_message_delivery(
+                        *id,
+                        MessageDirection::L1ToL2,
+                        log.transaction_hash,
+                        now,
+                    );
+                }
+                rw.l2_last_sync_block = range.synced;
+                rw.record_l2_delivered_messages(&executed_msgs);
             }
 
-            from = to + 1u64;
+            from = range.to + 1u64;
         }
 
-        if last_to_block != U64::zero() {
-            let mut rw = self.rw.lock().await;
-            rw.l2_last_sync_block = last_to_block;
-            rw.l2_delivered_messages.extend_from_slice(&executed_msgs);
-        }
+        Ok(())
     }
 
-    /// keeps track of L2 > L1 message events
-    async fn record_l2_messages(&self, block_hash: H256) {
+    /// Returns the L2 > L1 message events of `block_hash` with their transaction hash.
+    async fn fetch_l2_messages(
+        &self,
+        block_hash: H256,
+    ) -> Result<Vec<(MessageBeacon, Option<H256>)>, CoordinatorError> {
         let filter = Filter::new()
             .address(ValueOrArray::Value(self.ro.l2_message_dispatcher_addr))
             .topic0(ValueOrArray::Value(self.ro.message_dispatched_topic))
             .at_block_hash(block_hash);
-        let logs: Vec<Log> = self
-            .request_l2("eth_getLogs", [&filter])
-            .await
-            .expect("eth_getLogs");
+        let logs: Vec<Log> = self.request_l2("eth_getLogs", [&filter]).await?;
 
         log::trace!("L2: {} relay events for {}", logs.len(), block_hash);
         let mut pending = vec![];
         for log in logs {
+            let tx_hash = log.transaction_hash;
             let beacon = self._parse_message_beacon(log);
             log::info!("L1Relay: {:?}", beacon.id);
             log::debug!("{:?}", beacon);
-            pending.push(beacon);
+            pending.push((beacon, tx_hash));
         }
 
-        let mut rw = self.rw.lock().await;
-        rw.l2_message_queue.extend(pending);
+        Ok(pending)
     }
 
-    pub async fn relay_to_l1(&self) {
-        let mut rw = self.rw.lock().await;
-        let len = rw.l2_message_queue.len();
-
-        if len == 0 {
-            return;
-        }
-
-        // TODO: we are going to lose messages if we panic below
-        let todo: Vec<MessageBeacon> = rw.l2_message_queue.drain(0..cmp::min(32, len)).collect();
-        drop(rw);
+    /// Delivers up to 32 messages of `l2_message_queue` to L1.
+    /// A message is only removed from the queue once it was delivered or dropped.
+    /// A message that fails with a transient error stays in the queue without blocking
+    /// the messages behind it, the first such error is returned after all messages.
+    pub async fn relay_to_l1(&self) -> Result<(), CoordinatorError> {
+        let todo: Vec<MessageBeacon> = self
+            .rw
+            .lock()
+            .await
+            .l2_message_queue
+            .iter()
+            .take(32)
+            .cloned()
+            .collect();
 
-        const LOG_TAG: &str = "L1:deliverMessageWithProof:";
-        let l1_bridge_addr = Some(self.config.lock().await.l1_bridge);
+        let mut first_err = None;
         for msg in todo {
-            {
-                // check deadline
-                let ts_with_padding = U256::from(timestamp() + 900);
-                if msg.deadline < ts_with_padding {
-                    log::info!("{} {:?} deadline exceeded", LOG_TAG, msg.id);
-                    log::debug!("{:?}", msg);
+            let id = msg.id;
+            let reason = match self.relay_message_to_l1(msg).await {
+                Ok(reason) => reason,
+                Err(err) if err.is_transient() => {
+                    log::warn!("L1:deliverMessageWithProof: {:?} {}", id, err);
+                    first_err.get_or_insert(err);
                     continue;
                 }
+                Err(err) => {
+                    // retrying doesn't help, drop the message
+                    log::warn!("L1:deliverMessageWithProof: dropping {:?} {}", id, err);
+                    Some(DropReason::RelayFailed(err.to_string()))
+                }
+            };
+
+            let mut rw = self.rw.lock().await;
+            rw.l2_message_queue.retain(|e| e.id != id);
+            if let Some(reason) = reason {
+                let block_number = rw.l1_last_sync_block;
+                rw.record_dropped_messages([DroppedMessage {
+                    id,
+                    reason,
+                    block_number,
+                    timestamp: timestamp(),
+                }]);
             }
+            self.update_queue_metrics(&rw);
+        }
 
-            let found = self
-                .rw
-                .lock()
-                .await
-                .l1_delivered_messages
-                .iter()
-                .any(|&e| e == msg.id);
+        match first_err {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
 
-            log::trace!("{} skip={} {:?}", LOG_TAG, found, msg.id);
-            log::debug!("{:?}", msg);
-            if found {
-                continue;
+    /// Delivers `msg` to L1 unless it was already delivered.
+    /// Returns the reason if the message can't be delivered anymore.
+    async fn relay_message_to_l1(
+        &self,
+        msg: MessageBeacon,
+    ) -> Result<Option<DropReason>, CoordinatorError> {
+        const LOG_TAG: &str = "L1:deliverMessageWithProof:";
+        {
+            // check deadline
+            let ts_with_padding = U256::from(timestamp() + 900);
+            if msg.deadline < ts_with_padding {
+                log::info!("{} {:?} deadline exceeded", LOG_TAG, msg.id);
+                log::debug!("{:?}", msg);
+                return Ok(Some(DropReason::DeadlineExceeded));
             }
+        }
 
-            // latest state root known on L1
-            let state_root = self.state_root_l1().await.expect("l1.stateRoot");
-            log::trace!("L1:stateRoot: {:?}", state_root);
-
-            // latest finalized block hash, should include `state_root`
-            let block_hash = self.rw.lock().await.chain_state.finalized_block_hash;
-
-            // calculate the storage slot for this message
-            let storage_slot = msg.storage_slot();
-            // request proof
-            let proof_obj: ProofRequest = self
-                .request_l2(
-                    "eth_getProof",
-                    (
-                        self.ro.l2_message_dispatcher_addr,
-                        [storage_slot],
-                        block_hash,
-                    ),
-                )
-                .await
-                .expect("eth_getProof");
-
-            // encode proof and send it
-            let proof: Bytes = Bytes::from(marshal_proof(
-                &proof_obj.account_proof,
-                &proof_obj.storage_proof[0].proof,
-            ));
-            let calldata = self
-                .ro
-                .bridge_abi
-                .function("deliverMessageWithProof")
-                .unwrap()
-                .encode_input(&[
-                    msg.from.into_token(),
-                    msg.to.into_token(),
-                    msg.value.into_token(),
-                    msg.fee.into_token(),
-                    msg.deadline.into_token(),
-                    msg.nonce.into_token(),
-                    Token::Bytes(msg.calldata),
-                    proof.into_token(),
-                ])
-                .expect("calldata");
-            self.transaction_to_l1(l1_bridge_addr, U256::zero(), calldata)
-                .await
-                .expect("receipt");
+        let found = self
+            .rw
+            .lock()
+            .await
+            .l1_delivered_messages
+            .iter()
+            .any(|&e| e == msg.id);
+
+        log::trace!("{} skip={} {:?}", LOG_TAG, found, msg.id);
+        log::debug!("{:?}", msg);
+        if found {
+            return Ok(None);
         }
+
+        // latest state root known on L1
+        let state_root = self.state_root_l1().await?;
+        log::trace!("L1:stateRoot: {:?}", state_root);
+
+        // latest finalized block hash, should include `state_root`
+        let block_hash = self.rw.lock().await.chain_state.finalized_block_hash;
+
+        // calculate the storage slot for this message
+        let storage_slot = msg.storage_slot();
+        // request proof
+        let proof_obj: ProofRequest = self
+            .request_l2(
+                "eth_getProof",
+                (
+                    self.ro.l2_message_dispatcher_addr,
+                    [storage_slot],
+                    block_hash,
+                ),
+            )
+            .await?;
+        let storage_proof = proof_obj
+            .storage_proof
+            .get(0)
+            .ok_or_else(|| CoordinatorError::Invariant(format!("{} no storage proof", LOG_TAG)))?;
+
+        // encode proof and send it
+        let proof: Bytes = Bytes::from(marshal_proof(
+            &proof_obj.account_proof,
+            &storage_proof.proof,
+        ));
+        let calldata = self
+            .ro
+            .bridge_abi
+            .function("deliverMessageWithProof")
+            .unwrap()
+            .encode_input(&[
+                msg.from.into_token(),
+                msg.to.into_token(),
+                msg.value.into_token(),
+                msg.fee.into_token(),
+                msg.deadline.into_token(),
+                msg.nonce.into_token(),
+                Token::Bytes(msg.calldata),
+                proof.into_token(),
+            ])
+            .expect("calldata");
+        let l1_bridge_addr = Some(self.config.lock().await.l1_bridge);
+        self.transaction_to_l1_from(
+            &self.ro.l1_relayer_tx_manager,
+            l1_bridge_addr,
+            U256::zero(),
+            calldata,
+        )
+        .await?;
+
+        Ok(None)
     }
 
     fn _parse_message_beacon(&self, log: Log) -> MessageBeacon {
@@ -999,7 +1953,7 @@ impl SharedState {
         }
     }
 
-    async fn state_root_l1(&self) -> Result<H256, String> {
+    async fn state_root_l1(&self) -> Result<H256, CoordinatorError> {
         let calldata = Bytes::from(
             self.ro
                 .bridge_abi
@@ -1009,14 +1963,14 @@ impl SharedState {
                 .expect("calldata"),
         );
         let l1_bridge_addr = self.config.lock().await.l1_bridge;
-        let resp: Result<H256, String> = self
+        let state_root = self
             .request_l1(
                 "eth_call",
                 serde_json::json!([{ "to": l1_bridge_addr, "data": calldata }, "latest"]),
             )
-            .await;
+            .await?;
 
-        resp
+        Ok(state_root)
     }
 
     /// TODO: WIP - moved from prover/inputs
@@ -1024,8 +1978,7 @@ impl SharedState {
     pub async fn request_witness(&self, block_num: &U64) -> Result<Witness, String> {
         let block: Block<Transaction> = self
             .request_l2("eth_getBlockByNumber", (block_num, true))
-            .await
-            .expect("block");
+            .await?;
         let mut history_hashes = vec![H256::zero(); 256];
         let mut block_hash = block.parent_hash;
         history_hashes[255] = block_hash;
@@ -1048,6 +2001,15 @@ impl SharedState {
     }
 
     pub async fn request_proof(&self, block_num: &U64) -> Result<Option<Proofs>, String> {
+        self.request_proof_range(block_num, block_num).await
+    }
+
+    /// Requests one proof for the blocks `from..=to`, see `ProofRequestOptions::to_block`.
+    pub async fn request_proof_range(
+        &self,
+        block_num: &U64,
+        to_block: &U64,
+    ) -> Result<Option<Proofs>, String> {
         if self.config.lock().await.dummy_prover {
             log::warn!("COORDINATOR_DUMMY_PROVER");
             return Ok(Some(Proofs::default()));
@@ -1055,9 +2017,11 @@ impl SharedState {
 
         let config = self.config.lock().await;
         let prover_rpcd_url = config.prover_rpcd_url.clone();
+        let prover_rpcd_auth_token = config.prover_rpcd_auth_token.clone();
         let proof_options = ProofRequestOptions {
             circuit: config.circuit_name.clone(),
             block: block_num.as_u64(),
+            to_block: (to_block != block_num).then(|| to_block.as_u64()),
             rpc: config.l2_rpc_url.to_string(),
             retry: false,
             param: config.params_path.clone(),
@@ -1065,29 +2029,93 @@ impl SharedState {
             aggregate: config.aggregate_proof,
             mock_feedback: config.mock_prover_if_error,
             verify_proof: config.verify_proof,
+            priority: None,
         };
         drop(config);
 
-        let resp = jsonrpc_request_client(
+        self.rw
+            .lock()
+            .await
+            .proof_requested_at
+            .entry(*block_num)
+            .or_insert_with(Instant::now);
+
+        let resp = jsonrpc_call_with_token(
             RPC_REQUEST_TIMEOUT,
             &self.ro.http_client,
             &prover_rpcd_url,
+            prover_rpcd_auth_token.as_deref(),
             "proof",
             [proof_options],
         )
         .await;
 
         match resp {
+            // ...not an error
+            Err(JsonRpcRequestError::NoResult) => Ok(None),
             Err(err) => {
-                match err.as_ref() {
-                    "no result in response" => {
-                        // ...not an error
-                        Ok(None)
-                    }
-                    _ => Err(err),
+                self.ro.metrics.proof_request_failures.inc();
+                Err(err.into())
+            }
+            Ok(val) => {
+                let requested_at = self.rw.lock().await.proof_requested_at.remove(block_num);
+                if let Some(requested_at) = requested_at {
+                    self.ro
+                        .metrics
+                        .proof_latency
+                        .observe(requested_at.elapsed().as_secs_f64());
                 }
+
+                Ok(Some(val))
             }
-            Ok(val) => Ok(Some(val)),
+        }
+    }
+
+    /// Writes a snapshot of `rw` to the state store, if any.
+    /// The write happens in the background and only if the snapshot changed,
+    /// errors are only logged.
+    /// Events and messages are replayed from this state after a restart:
+    /// L1 and L2 are synced from the persisted sync progress before anything is relayed,
+    /// therefore a message that was delivered after the snapshot is
+    /// recorded as delivered before it could be relayed again.
+    pub async fn save_state(&self) {
+        if let Some(tx) = &self.ro.state_store_tx {
+            // the stages save concurrently, sending while holding the lock
+            // keeps an older snapshot from replacing a newer one
+            let rw = self.rw.lock().await;
+            tx.send_replace(Arc::new(rw.snapshot()));
+        }
+    }
+
+    /// Samples the message queue gauges from `rw`.
+    fn update_queue_metrics(&self, rw: &RwState) {
+        let metrics = &self.ro.metrics;
+        metrics
+            .message_queue
+            .with_label_values(&["l1_message_queue"])
+            .set(rw.l1_message_queue.len() as f64);
+        metrics
+            .message_queue
+            .with_label_values(&["l2_message_queue"])
+            .set(rw.l2_message_queue.len() as f64);
+    }
+
+    fn observe_l1_gas_per_block(&self, receipt: &TransactionReceipt, blocks: usize) {
+        if let Some(gas_used) = receipt.gas_used {
+            self.ro
+                .metrics
+                .l1_gas_per_block
+                .observe(gas_used.as_u64() as f64 / blocks as f64);
+        }
+    }
+
+    fn observe_l1_gas(&self, method: &str, receipt: &TransactionReceipt) {
+        if let Some(gas_used) = receipt.gas_used {
+            self.ro
+                .metrics
+                .l1_gas_used
+                .with_label_values(&[method])
+                .observe(gas_used.as_u64() as f64);
         }
     }
 
@@ -1098,9 +2126,14 @@ impl SharedState {
 
     /// Sets the coordinator configuration.
     /// Not all changes to the config may be reflected.
-    pub async fn set_config(&self, config: Config) {
+    pub async fn set_config(&self, mut config: Config) {
         // TODO: doesn't update all config values at the moment.
-        *self.config.lock().await = config;
+        let mut current = self.config.lock().await;
+        // not part of the serialized config
+        config.rpc_auth_token = current.rpc_auth_token.take();
+        config.rpc_auth_read_token = current.rpc_auth_read_token.take();
+        config.prover_rpcd_auth_token = current.prover_rpcd_auth_token.take();
+        *current = config;
     }
 }
 
@@ -1112,7 +2145,9 @@ fn get_abi() -> Abi {
             "event MessageDispatched(address from, address to, uint256 value, uint256 fee, uint256 deadline, uint256 nonce, bytes data)",
             "event MessageDelivered(bytes32 id)",
             "function submitBlock(bytes)",
+            "function submitBlocks(bytes[])",
             "function finalizeBlock(bytes proof)",
+            "function finalizeBlocks(bytes32[] blockHashes, bytes proof)",
             "function deliverMessageWithProof(address from, address to, uint256 value, uint256 fee, uint256 deadline, uint256 nonce, bytes data, bytes proof)",
             "function stateRoot() returns (bytes32)",
             "function importBlockHeader(uint256 blockNumber, bytes32 blockHash, bytes blockHeader)",
@@ -1121,6 +2156,92 @@ fn get_abi() -> Abi {
         .expect("parse abi")
 }
 
+/// Returns the `proof` argument of `finalizeBlock` and `finalizeBlocks` for `block_hash`.
+/// The aggregation proof is used if not empty.
+fn finalize_proof_data(block_hash: H256, proof: Proofs) -> Bytes {
+    let (is_aggregated, proof_result) = {
+        if proof.aggregation.proof.len() != 0 {
+            (true, proof.aggregation)
+        } else {
+            (false, proof.circuit)
+        }
+    };
+
+    let mut verifier_calldata = vec![];
+    let mut tmp_buf = vec![0u8; 32];
+
+    proof_result.instance.iter().for_each(|v| {
+        v.to_big_endian(&mut tmp_buf);
+        verifier_calldata.extend_from_slice(&tmp_buf);
+    });
+    verifier_calldata.extend_from_slice(proof_result.proof.as_ref());
+
+    let mut proof_data = vec![];
+    proof_data.extend_from_slice(block_hash.as_ref());
+
+    // this is temporary until proper contract setup
+    let verifier_addr = U256::from(proof_result.label.as_bytes());
+    verifier_addr.to_big_endian(&mut tmp_buf);
+    proof_data.extend_from_slice(&tmp_buf);
+
+    let is_aggregated = match is_aggregated {
+        true => U256::one(),
+        false => U256::zero(),
+    };
+    is_aggregated.to_big_endian(&mut tmp_buf);
+    proof_data.extend_from_slice(&tmp_buf);
+
+    proof_data.extend_from_slice(&verifier_calldata);
+
+    Bytes::from(proof_data)
+}
+
+/// Returns the hashes of the submitted blocks in the calldata
+/// of a `submitBlock` or `submitBlocks` transaction.
+fn submitted_block_hashes(abi: &Abi, tx_data: &[u8]) -> Result<Vec<H256>, String> {
+    if tx_data.len() < 4 {
+        return Err("calldata too short".to_string());
+    }
+    let (selector, input) = tx_data.split_at(4);
+    let submit_block = abi.function("submitBlock").unwrap();
+    let submit_blocks = abi.function("submitBlocks").unwrap();
+    let witnesses = if selector == submit_block.short_signature() {
+        submit_block
+            .decode_input(input)
+            .map_err(|e| e.to_string())?
+    } else if selector == submit_blocks.short_signature() {
+        match submit_blocks.decode_input(input) {
+            Ok(mut tokens) => match tokens.pop() {
+                Some(Token::Array(witnesses)) => witnesses,
+                _ => return Err("expected bytes[]".to_string()),
+            },
+            Err(err) => return Err(err.to_string()),
+        }
+    } else {
+        return Err(format!("unknown function {:?}", selector));
+    };
+
+    witnesses
+        .into_iter()
+        .map(|token| {
+            let witness = token.into_bytes().ok_or("expected bytes")?;
+            block_header_hash(&witness)
+        })
+        .collect()
+}
+
+/// Returns the hash of the block header at the start of `witness`.
+fn block_header_hash(witness: &[u8]) -> Result<H256, String> {
+    let rlp = rlp::Rlp::new(witness);
+    let info = rlp.payload_info().map_err(|e| format!("{:?}", e))?;
+    let block_header = rlp
+        .as_raw()
+        .get(0..info.header_len + info.value_len)
+        .ok_or("block header out of bounds")?;
+
+    Ok(H256::from(keccak256(block_header)))
+}
+
 fn timestamp() -> u64 {
     SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -1128,13 +2249,13 @@ fn timestamp() -> u64 {
         .as_secs()
 }
 
-async fn get_wallet(rcp_url: &Uri, sign_key: &str) -> LocalWallet {
+/// Returns the signer for `sign_key`, see `TxSigner::from_spec`.
+async fn get_wallet(rcp_url: &Uri, sign_key: &str, password: Option<&str>) -> TxSigner {
     let chain_id: U64 = jsonrpc_request(rcp_url, "eth_chainId", ())
         .await
         .expect("chain id L1");
 
-    sign_key
-        .parse::<LocalWallet>()
-        .expect("cannot create LocalWallet from private key")
-        .with_chain_id(chain_id.as_u64())
+    TxSigner::from_spec(sign_key, chain_id.as_u64(), password)
+        .await
+        .expect("signer")
 }
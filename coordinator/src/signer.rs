@@ -0,0 +1,131 @@
+use crate::utils::RPC_REQUEST_TIMEOUT;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::{Address, Bytes};
+use ethers_signers::{LocalWallet, Signer};
+use hyper::client::HttpConnector;
+use hyper::Uri;
+use serde::Deserialize;
+use zkevm_common::json_rpc::jsonrpc_request_client;
+
+/// Signs the transactions of a coordinator wallet.
+#[derive(Clone, Debug)]
+pub enum TxSigner {
+    /// A private key in memory, either from the config or a keystore file.
+    Local(LocalWallet),
+    /// An external signer like clef or web3signer, see `RemoteSigner`.
+    Remote(RemoteSigner),
+}
+
+/// Signs transactions with `eth_signTransaction` of an external signer.
+#[derive(Clone, Debug)]
+pub struct RemoteSigner {
+    pub uri: Uri,
+    pub address: Address,
+    pub chain_id: u64,
+    client: hyper::Client<HttpConnector>,
+}
+
+/// The response of `eth_signTransaction`, either the raw transaction
+/// or an object with the raw transaction like geth and clef return.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SignTransactionResponse {
+    Raw(Bytes),
+    Object { raw: Bytes },
+}
+
+impl TxSigner {
+    /// Returns the signer for `spec`:
+    /// - `keystore:<path>` an encrypted keystore file, decrypted with `password`
+    /// - `remote:<url>` an external signer, signs for the first account of `eth_accounts`
+    /// - otherwise a hex encoded private key
+    pub async fn from_spec(
+        spec: &str,
+        chain_id: u64,
+        password: Option<&str>,
+    ) -> Result<Self, String> {
+        if let Some(path) = spec.strip_prefix("keystore:") {
+            let password = password.ok_or("keystore password required")?;
+            let wallet = LocalWallet::decrypt_keystore(path, password)
+                .map_err(|e| format!("keystore {}: {}", path, e))?;
+
+            return Ok(Self::Local(wallet.with_chain_id(chain_id)));
+        }
+
+        if let Some(url) = spec.strip_prefix("remote:") {
+            let uri = Uri::try_from(url).map_err(|e| e.to_string())?;
+            let client = hyper::Client::new();
+            let accounts: Vec<Address> =
+                jsonrpc_request_client(RPC_REQUEST_TIMEOUT, &client, &uri, "eth_accounts", ())
+                    .await?;
+            let address = *accounts
+                .first()
+                .ok_or_else(|| format!("remote signer {}: no accounts", url))?;
+
+            return Ok(Self::Remote(RemoteSigner {
+                uri,
+                address,
+                chain_id,
+                client,
+            }));
+        }
+
+        let wallet = spec
+            .parse::<LocalWallet>()
+            .map_err(|_| "cannot create LocalWallet from private key".to_string())?;
+
+        Ok(Self::Local(wallet.with_chain_id(chain_id)))
+    }
+
+    pub fn address(&self) -> Address {
+        match self {
+            Self::Local(wallet) => wallet.address(),
+            Self::Remote(signer) => signer.address,
+        }
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Self::Local(wallet) => wallet.chain_id(),
+            Self::Remote(signer) => signer.chain_id,
+        }
+    }
+
+    /// Signs `tx` and returns the raw signed transaction.
+    pub async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Bytes, String> {
+        match self {
+            Self::Local(wallet) => {
+                let sig = wallet
+                    .sign_transaction(tx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+
+                Ok(tx.rlp_signed(&sig))
+            }
+            Self::Remote(signer) => {
+                let mut tx = tx.clone();
+                tx.set_from(signer.address);
+                tx.set_chain_id(signer.chain_id);
+                let resp: SignTransactionResponse = jsonrpc_request_client(
+                    RPC_REQUEST_TIMEOUT,
+                    &signer.client,
+                    &signer.uri,
+                    "eth_signTransaction",
+                    [&tx],
+                )
+                .await?;
+
+                match resp {
+                    SignTransactionResponse::Raw(raw) => Ok(raw),
+                    SignTransactionResponse::Object { raw } => Ok(raw),
+                }
+            }
+        }
+    }
+}
+
+impl From<LocalWallet> for TxSigner {
+    fn from(wallet: LocalWallet) -> Self {
+        Self::Local(wallet)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::structs::DroppedMessage;
+use crate::structs::ForkchoiceStateV1;
+use crate::structs::L1BlockRecord;
+use crate::structs::MessageBeacon;
+use crate::structs::MessageRecord;
+use ethers_core::types::{H256, U64};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// The schema version of `Snapshot`, must be increased on incompatible changes.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The part of `RwState` that is required to resume after a restart.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub chain_state: ForkchoiceStateV1,
+    pub l1_last_sync_block: U64,
+    pub l2_last_sync_block: U64,
+    pub l1_message_queue: VecDeque<MessageBeacon>,
+    #[serde(default)]
+    pub l1_message_deadlines: HashMap<H256, u64>,
+    #[serde(default)]
+    pub dropped_messages: VecDeque<DroppedMessage>,
+    #[serde(default)]
+    pub message_records: VecDeque<MessageRecord>,
+    pub l2_delivered_messages: Vec<H256>,
+    pub l2_message_queue: Vec<MessageBeacon>,
+    pub l1_delivered_messages: Vec<H256>,
+    #[serde(default)]
+    pub l1_blocks: VecDeque<L1BlockRecord>,
+}
+
+/// Stores the latest `Snapshot` as a json file inside a directory.
+pub struct StateStore {
+    path: PathBuf,
+    /// the json of the last saved snapshot
+    last: Mutex<Vec<u8>>,
+}
+
+impl StateStore {
+    /// Creates `dir` if it doesn't exist yet.
+    pub fn new(dir: &str) -> Result<Self, String> {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            path: PathBuf::from(dir).join("state.json"),
+            last: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Returns the snapshot written by a previous `save`, if any.
+    /// Fails if the snapshot has a different schema version.
+    pub fn load(&self) -> Result<Option<Snapshot>, String> {
+        let file = match File::open(&self.path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.to_string()),
+            Ok(file) => file,
+        };
+        let snapshot: serde_json::Value =
+            serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string())?;
+        let version = snapshot.get("version").and_then(|e| e.as_u64());
+        if version != Some(SNAPSHOT_VERSION as u64) {
+            return Err(format!(
+                "{:?}: unsupported snapshot version {:?}, expected {}",
+                self.path, version, SNAPSHOT_VERSION
+            ));
+        }
+
+        serde_json::from_value(snapshot)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Replaces the stored snapshot with `snapshot`.
+    /// Skips the write if `snapshot` didn't change since the last `save`.
+    pub fn save(&self, snapshot: &Snapshot) -> Result<(), String> {
+        let json = serde_json::to_vec(snapshot).map_err(|e| e.to_string())?;
+        let mut last = self.last.lock().unwrap();
+        if *last == json {
+            return Ok(());
+        }
+
+        // write to a temporary file first and replace the old file afterwards,
+        // a crash in between leaves the previous version intact.
+        let tmp_path = self.path.with_extension("json.tmp");
+        let mut file = File::create(&tmp_path).map_err(|e| e.to_string())?;
+        file.write_all(&json).map_err(|e| e.to_string())?;
+        file.sync_all().map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp_path, &self.path).map_err(|e| e.to_string())?;
+        *last = json;
+
+        Ok(())
+    }
+}
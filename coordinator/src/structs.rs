@@ -1,5 +1,6 @@
 use ethers_core::types::{Address, Bytes, H256, U256, U64};
 use ethers_core::utils::keccak256;
+use std::collections::HashMap;
 
 #[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub struct ForkchoiceStateV1 {
@@ -11,7 +12,7 @@ pub struct ForkchoiceStateV1 {
     pub finalized_block_hash: H256,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct MessageBeacon {
     pub id: H256,
     pub from: Address,
@@ -37,6 +38,148 @@ impl MessageBeacon {
     }
 }
 
+/// Why a message was removed from `l1_message_queue` or `l2_message_queue`
+/// without being delivered.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", content = "error", rename_all = "camelCase")]
+pub enum DropReason {
+    /// The `deadline` of the message passed.
+    DeadlineExceeded,
+    /// The delivery transaction failed against the pending block.
+    SimulationFailed(String),
+    /// The block with the delivery transaction failed, probably a revert.
+    BlockFailed(String),
+    /// The delivery does not fit into a provable block on its own.
+    TooLarge,
+    /// The delivery of a L2 > L1 message failed with an error that retrying won't fix.
+    RelayFailed(String),
+}
+
+impl DropReason {
+    /// Classifies the error `err` of sealing a block with the delivery of a L1 > L2 message.
+    /// Returns `None` if the block is full and the message should wait for the next one,
+    /// or `TooLarge` if the block has no other messages.
+    pub fn from_block_error(err: String, first_message: bool) -> Option<Self> {
+        match err.as_str() {
+            // doesn't fit into any block
+            "gas limit reached" if first_message => Some(Self::TooLarge),
+            "gas limit reached" => None,
+            // another error, probably a revert
+            _ => Some(Self::BlockFailed(err)),
+        }
+    }
+}
+
+/// A message that was dropped, see `RwState::dropped_messages`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedMessage {
+    pub id: H256,
+    pub reason: DropReason,
+    /// The number of the block the delivery was attempted for, the L2 block
+    /// for L1 > L2 messages and the last synced L1 block for L2 > L1 messages.
+    pub block_number: U64,
+    /// The timestamp of that block.
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MessageDirection {
+    L1ToL2,
+    L2ToL1,
+}
+
+/// The bridge events of a message, see `RwState::message_records`.
+/// The timestamps are the times the coordinator synced the events.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageRecord {
+    pub id: H256,
+    pub direction: MessageDirection,
+    /// The transaction with the `MessageDispatched` event.
+    pub dispatch_tx: Option<H256>,
+    pub dispatched_at: Option<u64>,
+    /// The transaction with the `MessageDelivered` event.
+    pub delivery_tx: Option<H256>,
+    pub delivered_at: Option<u64>,
+}
+
+/// The state of a bridge message, see `RwState::message_status`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MessageStatus {
+    /// The coordinator doesn't know the message (yet).
+    Unknown,
+    /// Waiting for the delivery, L1 > L2 messages are delivered before `inclusion_deadline`.
+    #[serde(rename_all = "camelCase")]
+    Queued {
+        inclusion_deadline: Option<u64>,
+    },
+    /// Removed from the queue without delivery.
+    #[serde(rename_all = "camelCase")]
+    Dropped {
+        reason: DropReason,
+        block_number: U64,
+    },
+    Delivered,
+}
+
+/// The response of the `messageStatus` and `pendingMessages` methods.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageStatusResponse {
+    pub id: H256,
+    pub status: MessageStatus,
+    /// The bridge events of the message if recorded.
+    pub record: Option<MessageRecord>,
+}
+
+/// The changes of the bridge events of a processed L1 block,
+/// used to roll them back if the block is reorged.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct L1BlockRecord {
+    pub number: U64,
+    pub hash: H256,
+    /// `chain_state.safe_block_hash` before the events of this block, if changed by them.
+    pub prev_safe_block_hash: Option<H256>,
+    /// `chain_state.finalized_block_hash` before the events of this block, if changed by them.
+    pub prev_finalized_block_hash: Option<H256>,
+    /// Ids of messages added to `l1_message_queue`.
+    pub l1_messages: Vec<H256>,
+    /// Ids of messages added to `l1_delivered_messages`.
+    pub l1_delivered_messages: Vec<H256>,
+    /// Ids of messages added to `l2_message_queue`.
+    pub l2_messages: Vec<H256>,
+}
+
+impl L1BlockRecord {
+    pub fn new(number: U64, hash: H256) -> Self {
+        Self {
+            number,
+            hash,
+            prev_safe_block_hash: None,
+            prev_finalized_block_hash: None,
+            l1_messages: Vec::new(),
+            l1_delivered_messages: Vec::new(),
+            l2_messages: Vec::new(),
+        }
+    }
+}
+
+/// The result of the `prestateTracer` for one transaction of `debug_traceBlockByHash`.
+#[derive(Debug, serde::Deserialize)]
+pub struct PrestateTrace {
+    /// The accounts touched by the transaction.
+    pub result: HashMap<Address, PrestateAccount>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PrestateAccount {
+    #[serde(default)]
+    pub code: Bytes,
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct SealBlockRequest<'a> {
     pub parent: &'a H256,
@@ -0,0 +1,433 @@
+use crate::error::CoordinatorError;
+use crate::signer::TxSigner;
+use crate::utils::RPC_REQUEST_TIMEOUT;
+use ethers_core::types::transaction::eip2930::AccessListWithGasUsed;
+use ethers_core::types::{
+    Address, Bytes, Eip1559TransactionRequest, TransactionReceipt, H256, U256, U64,
+};
+use ethers_core::utils::keccak256;
+use hyper::client::HttpConnector;
+use hyper::Uri;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cmp;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use zkevm_common::json_rpc::jsonrpc_call;
+use zkevm_common::json_rpc::jsonrpc_request_client;
+use zkevm_common::json_rpc::JsonRpcRequestError;
+
+/// The increase of both fees for a replacement transaction, in percent.
+/// Nodes reject replacements with less than 10%.
+pub const FEE_BUMP_PERCENT: u64 = 20;
+/// The maximum interval for polling receipts and the chain head.
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Errors of `TxManager::send`.
+#[derive(Clone, Debug)]
+pub enum TxError {
+    /// Preparing or broadcasting the transaction failed.
+    Rpc(String),
+    /// The gas estimation reverted, the transaction would revert as well.
+    EstimateReverted(String),
+    /// The transaction was included but reverted.
+    Reverted(TransactionReceipt),
+    /// No version of the transaction was included after all fee bumps,
+    /// contains the hash of the last broadcasted version.
+    /// Sending the same transaction again resumes waiting for it, see `TxManager::send`.
+    Timeout(H256),
+}
+
+impl fmt::Display for TxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rpc(err) => write!(f, "{}", err),
+            Self::EstimateReverted(err) => write!(f, "gas estimation reverted: {}", err),
+            Self::Reverted(receipt) => {
+                write!(f, "transaction reverted {:?}", receipt.transaction_hash)
+            }
+            Self::Timeout(tx_hash) => write!(f, "timeout waiting for transaction {:?}", tx_hash),
+        }
+    }
+}
+
+impl From<String> for TxError {
+    fn from(err: String) -> Self {
+        Self::Rpc(err)
+    }
+}
+
+impl From<JsonRpcRequestError> for TxError {
+    fn from(err: JsonRpcRequestError) -> Self {
+        match err {
+            JsonRpcRequestError::Response(err) if err.is_revert() => {
+                Self::EstimateReverted(err.message)
+            }
+            err => Self::Rpc(err.to_string()),
+        }
+    }
+}
+
+impl From<TxError> for CoordinatorError {
+    fn from(err: TxError) -> Self {
+        match err {
+            TxError::Rpc(err) => Self::Rpc(err),
+            TxError::EstimateReverted(_) | TxError::Reverted(_) => Self::Revert(err.to_string()),
+            TxError::Timeout(_) => Self::Rpc(err.to_string()),
+        }
+    }
+}
+
+impl From<TxError> for String {
+    fn from(err: TxError) -> Self {
+        err.to_string()
+    }
+}
+
+/// A broadcasted transaction that is not included yet.
+#[derive(Clone, Debug)]
+struct PendingTx {
+    tx: Eip1559TransactionRequest,
+    /// every broadcasted version, any of them can be included
+    tx_hashes: Vec<H256>,
+}
+
+/// Sends the transactions of a L1 wallet.
+///
+/// The nonce is tracked locally, thus concurrent sends get consecutive nonces
+/// instead of replacing each other. A transaction that is not included within
+/// `bump_timeout` is replaced by the same transaction with higher fees,
+/// see `bump_fees`. A transaction that was not included when `send` failed
+/// is kept and a later `send` of the same transaction continues with it,
+/// thus retries can't include it twice.
+pub struct TxManager {
+    wallet: TxSigner,
+    http_client: hyper::Client<HttpConnector>,
+    /// The time until a pending transaction is replaced.
+    pub bump_timeout: Duration,
+    /// The number of replacements before `send` gives up.
+    pub max_bumps: u32,
+    /// The number of blocks on top of the including block until a transaction is final.
+    pub confirmations: u64,
+    /// The next nonce, fetched from the node if `None` or lower than the pending nonce.
+    /// Also serializes the broadcast of new transactions.
+    nonce: Mutex<Option<U256>>,
+    /// The not yet included transactions by `pending_key`.
+    pending: Mutex<HashMap<H256, PendingTx>>,
+}
+
+impl TxManager {
+    pub fn new(
+        wallet: TxSigner,
+        bump_timeout: Duration,
+        max_bumps: u32,
+        confirmations: u64,
+    ) -> Self {
+        Self {
+            wallet,
+            http_client: hyper::Client::new(),
+            bump_timeout,
+            max_bumps,
+            confirmations,
+            nonce: Mutex::new(None),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.wallet.address()
+    }
+
+    /// Signs and broadcasts a transaction with the next nonce and waits until
+    /// it is included with `confirmations`. Returns the receipt of the included version.
+    /// If the same transaction is still pending from a failed `send`,
+    /// waits for that one instead.
+    pub async fn send(
+        &self,
+        node_uri: &Uri,
+        to: Option<Address>,
+        value: U256,
+        calldata: Vec<u8>,
+    ) -> Result<TransactionReceipt, TxError> {
+        let key = pending_key(to, value, &calldata);
+        let resumed = self.pending.lock().await.get(&key).cloned();
+        let pending = match resumed {
+            Some(pending) => {
+                log::info!(
+                    "resuming transaction {:?} nonce={:?}",
+                    pending.tx_hashes.last().unwrap(),
+                    pending.tx.nonce
+                );
+                pending
+            }
+            None => {
+                let mut nonce = self.nonce.lock().await;
+                // transactions of the same wallet that were sent by someone else
+                let pending: U256 = self
+                    .request(
+                        node_uri,
+                        "eth_getTransactionCount",
+                        (self.address(), "pending"),
+                    )
+                    .await?;
+                let next = cmp::max(nonce.unwrap_or_default(), pending);
+                let tx = self.prepare(node_uri, to, value, calldata, next).await?;
+                match self.broadcast(node_uri, &tx).await {
+                    Err(err) => {
+                        *nonce = None;
+                        return Err(err);
+                    }
+                    Ok(tx_hash) => {
+                        *nonce = Some(next + 1);
+                        let pending = PendingTx {
+                            tx,
+                            tx_hashes: vec![tx_hash],
+                        };
+                        self.pending.lock().await.insert(key, pending.clone());
+                        pending
+                    }
+                }
+            }
+        };
+
+        let res = self.wait(node_uri, key, pending).await;
+        match res {
+            // still pending, see `TxError::Timeout`
+            Err(TxError::Rpc(_)) | Err(TxError::Timeout(_)) => {}
+            _ => {
+                self.pending.lock().await.remove(&key);
+            }
+        }
+
+        res
+    }
+
+    /// Waits until any version of `pending` is included, replaces it with
+    /// higher fees after each `bump_timeout`.
+    async fn wait(
+        &self,
+        node_uri: &Uri,
+        key: H256,
+        mut pending: PendingTx,
+    ) -> Result<TransactionReceipt, TxError> {
+        let mut bumps = 0;
+        let mut bump_at = Instant::now() + self.bump_timeout;
+        let poll_interval = cmp::min(POLL_INTERVAL, self.bump_timeout);
+        loop {
+            tokio::time::sleep(poll_interval).await;
+
+            for tx_hash in pending.tx_hashes.iter() {
+                if let Some(receipt) = self.receipt(node_uri, tx_hash).await {
+                    return self.confirm(node_uri, receipt).await;
+                }
+            }
+
+            if Instant::now() < bump_at {
+                continue;
+            }
+            if bumps == self.max_bumps {
+                let nonce: U256 = self
+                    .request(
+                        node_uri,
+                        "eth_getTransactionCount",
+                        (self.address(), "latest"),
+                    )
+                    .await?;
+                if nonce > pending.tx.nonce.unwrap_or_default() {
+                    // the nonce is used, by this transaction if it was included in the meantime
+                    for tx_hash in pending.tx_hashes.iter() {
+                        if let Some(receipt) = self.receipt(node_uri, tx_hash).await {
+                            return self.confirm(node_uri, receipt).await;
+                        }
+                    }
+                    // or by another transaction, thus this one can be sent again
+                    self.pending.lock().await.remove(&key);
+                    *self.nonce.lock().await = None;
+                    return Err(TxError::Rpc(format!(
+                        "nonce {:?} of transaction {:?} was used by another transaction",
+                        pending.tx.nonce,
+                        pending.tx_hashes.last().unwrap()
+                    )));
+                }
+                return Err(TxError::Timeout(*pending.tx_hashes.last().unwrap()));
+            }
+            bumps += 1;
+            bump_at = Instant::now() + self.bump_timeout;
+
+            let gas_price: U256 = self.request(node_uri, "eth_gasPrice", ()).await?;
+            bump_fees(&mut pending.tx, gas_price);
+            log::info!(
+                "replacing transaction {:?} nonce={:?} max_fee_per_gas={:?}",
+                pending.tx_hashes.last().unwrap(),
+                pending.tx.nonce,
+                pending.tx.max_fee_per_gas
+            );
+            // fails if a previous version was included in the meantime
+            match self.broadcast(node_uri, &pending.tx).await {
+                Err(err) => log::warn!("replacement failed: {}", err),
+                Ok(tx_hash) => {
+                    pending.tx_hashes.push(tx_hash);
+                    self.pending.lock().await.insert(key, pending.clone());
+                }
+            }
+        }
+    }
+
+    /// Returns the transaction with gas limit and fees for the current network conditions.
+    async fn prepare(
+        &self,
+        node_uri: &Uri,
+        to: Option<Address>,
+        value: U256,
+        calldata: Vec<u8>,
+        nonce: U256,
+    ) -> Result<Eip1559TransactionRequest, TxError> {
+        let gas_price: U256 = self.request(node_uri, "eth_gasPrice", ()).await?;
+        // not supported by every node
+        let priority_fee: U256 = self
+            .request(node_uri, "eth_maxPriorityFeePerGas", ())
+            .await
+            .unwrap_or_else(|_| U256::one());
+        let max_fee = gas_price * 2u64;
+
+        let mut tx = Eip1559TransactionRequest::new()
+            .chain_id(self.wallet.chain_id())
+            .from(self.address())
+            .nonce(nonce)
+            .value(value)
+            .max_priority_fee_per_gas(cmp::min(priority_fee, max_fee))
+            .max_fee_per_gas(max_fee)
+            .data(calldata);
+
+        if let Some(to) = to {
+            tx = tx.to(to);
+        }
+
+        // both revert if the transaction would revert
+        let access_list: AccessListWithGasUsed =
+            self.call(node_uri, "eth_createAccessList", [&tx]).await?;
+        let tx = tx.access_list(access_list.access_list);
+        let estimate: U256 = self.call(node_uri, "eth_estimateGas", [&tx]).await?;
+
+        Ok(tx.gas(estimate))
+    }
+
+    /// Signs and broadcasts `tx`, returns the transaction hash.
+    async fn broadcast(
+        &self,
+        node_uri: &Uri,
+        tx: &Eip1559TransactionRequest,
+    ) -> Result<H256, TxError> {
+        log::debug!("sending l1 tx: {:?}", tx);
+
+        let raw_tx: Bytes = self.wallet.sign_transaction(&tx.clone().into()).await?;
+        let tx_hash = H256::from(keccak256(&raw_tx));
+
+        let resp: Result<H256, String> = self
+            .request(node_uri, "eth_sendRawTransaction", [&raw_tx])
+            .await;
+        match resp {
+            // a rebroadcast of the same transaction is fine
+            Err(err) if !err.contains("already known") => Err(TxError::Rpc(err)),
+            _ => Ok(tx_hash),
+        }
+    }
+
+    /// Waits until `receipt` has `confirmations` and checks that it is still included.
+    async fn confirm(
+        &self,
+        node_uri: &Uri,
+        mut receipt: TransactionReceipt,
+    ) -> Result<TransactionReceipt, TxError> {
+        if self.confirmations > 0 {
+            let tx_hash = receipt.transaction_hash;
+            loop {
+                let block_number = receipt.block_number.unwrap_or_default();
+                let head: U64 = self.request(node_uri, "eth_blockNumber", ()).await?;
+                if head >= block_number + self.confirmations {
+                    break;
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            // the including block may have been reorged in the meantime
+            receipt = self
+                .receipt(node_uri, &tx_hash)
+                .await
+                .ok_or_else(|| TxError::Rpc(format!("transaction {:?} was reorged", tx_hash)))?;
+        }
+
+        if receipt.status != Some(U64::one()) {
+            return Err(TxError::Reverted(receipt));
+        }
+
+        Ok(receipt)
+    }
+
+    async fn receipt(&self, node_uri: &Uri, tx_hash: &H256) -> Option<TransactionReceipt> {
+        self.request(node_uri, "eth_getTransactionReceipt", [tx_hash])
+            .await
+            .ok()
+    }
+
+    async fn request<T: Serialize + Send + Sync, R: DeserializeOwned>(
+        &self,
+        node_uri: &Uri,
+        method: &str,
+        params: T,
+    ) -> Result<R, String> {
+        jsonrpc_request_client(
+            RPC_REQUEST_TIMEOUT,
+            &self.http_client,
+            node_uri,
+            method,
+            params,
+        )
+        .await
+    }
+
+    /// Same as `request` but keeps the error response of the node.
+    async fn call<T: Serialize + Send + Sync, R: DeserializeOwned>(
+        &self,
+        node_uri: &Uri,
+        method: &str,
+        params: T,
+    ) -> Result<R, JsonRpcRequestError> {
+        jsonrpc_call(
+            RPC_REQUEST_TIMEOUT,
+            &self.http_client,
+            node_uri,
+            method,
+            params,
+        )
+        .await
+    }
+}
+
+/// Identifies a transaction independent of its nonce and fees.
+fn pending_key(to: Option<Address>, value: U256, calldata: &[u8]) -> H256 {
+    let mut buf = vec![to.is_some() as u8];
+    buf.extend_from_slice(to.unwrap_or_default().as_bytes());
+    let mut tmp_buf = [0u8; 32];
+    value.to_big_endian(&mut tmp_buf);
+    buf.extend_from_slice(&tmp_buf);
+    buf.extend_from_slice(calldata);
+
+    H256::from(keccak256(buf))
+}
+
+/// Increases both fees of `tx` by `FEE_BUMP_PERCENT`.
+/// The maximum fee is at least twice the current `gas_price`.
+pub fn bump_fees(tx: &mut Eip1559TransactionRequest, gas_price: U256) {
+    let bump = |fee: U256| cmp::max(fee * (100 + FEE_BUMP_PERCENT) / 100, fee + 1u64);
+    let priority_fee = bump(tx.max_priority_fee_per_gas.unwrap_or_default());
+    let max_fee = cmp::max(
+        bump(tx.max_fee_per_gas.unwrap_or_default()),
+        gas_price * 2u64,
+    );
+
+    tx.max_priority_fee_per_gas = Some(cmp::min(priority_fee, max_fee));
+    tx.max_fee_per_gas = Some(max_fee);
+}
@@ -1,5 +1,5 @@
+use crate::signer::TxSigner;
 use crate::structs::*;
-use crate::timeout;
 use ethers_core::types::transaction::eip2930::AccessListWithGasUsed;
 use ethers_core::types::Transaction;
 use ethers_core::types::{
@@ -8,53 +8,29 @@ use ethers_core::types::{
 };
 use ethers_core::utils::keccak256;
 use ethers_core::utils::rlp::RlpStream;
-use ethers_signers::{LocalWallet, Signer};
 use hyper::client::HttpConnector;
 use hyper::Uri;
+use std::cmp;
+use std::collections::HashSet;
+use zkevm_common::circuit_autogen::CIRCUIT_CONFIGS;
 use zkevm_common::json_rpc::jsonrpc_request_client;
 
 pub const RPC_REQUEST_TIMEOUT: u64 = 30000;
 
-/// may override any pending transactions
-pub async fn send_transaction_to_l1(
-    client: &hyper::Client<HttpConnector>,
-    node_uri: &Uri,
-    wallet: &LocalWallet,
-    to: Option<Address>,
-    value: U256,
-    calldata: Vec<u8>,
-) -> Result<TransactionReceipt, String> {
-    let nonce: U256 = jsonrpc_request_client(
-        RPC_REQUEST_TIMEOUT,
-        client,
-        node_uri,
-        "eth_getTransactionCount",
-        (wallet.address(), "latest"),
-    )
-    .await
-    .expect("nonce");
-
-    let raw_tx = sign_transaction_l1(client, node_uri, wallet, to, value, calldata, nonce).await;
-    // wait up to 120 seconds
-    timeout!(120_000, wait_for_tx(client, node_uri, &raw_tx).await)
-}
-
 /// may override any pending transactions
 pub async fn sign_transaction_l1(
     client: &hyper::Client<HttpConnector>,
     node_uri: &Uri,
-    wallet: &LocalWallet,
+    wallet: &TxSigner,
     to: Option<Address>,
     value: U256,
     calldata: Vec<u8>,
     nonce: U256,
-) -> Bytes {
+) -> Result<Bytes, String> {
     let wallet_addr: Address = wallet.address();
 
     let gas_price: U256 =
-        jsonrpc_request_client(RPC_REQUEST_TIMEOUT, client, node_uri, "eth_gasPrice", ())
-            .await
-            .expect("gasPrice");
+        jsonrpc_request_client(RPC_REQUEST_TIMEOUT, client, node_uri, "eth_gasPrice", ()).await?;
 
     let mut tx: Eip1559TransactionRequest = Eip1559TransactionRequest::new()
         .chain_id(wallet.chain_id())
@@ -76,8 +52,7 @@ pub async fn sign_transaction_l1(
         "eth_createAccessList",
         [&tx],
     )
-    .await
-    .expect("eth_createAccessList");
+    .await?;
     let tx = tx.access_list(access_list.access_list);
     let estimate: U256 = jsonrpc_request_client(
         RPC_REQUEST_TIMEOUT,
@@ -86,25 +61,19 @@ pub async fn sign_transaction_l1(
         "eth_estimateGas",
         [&tx],
     )
-    .await
-    .expect("eth_estimateGas");
+    .await?;
     let tx = tx.gas(estimate).into();
 
     log::debug!("sending l1 tx: {:?}", tx);
 
-    let sig = wallet
-        .sign_transaction(&tx)
-        .await
-        .expect("sign_transaction");
-
-    tx.rlp_signed(&sig)
+    wallet.sign_transaction(&tx).await
 }
 
 /// may override any pending transactions
 pub async fn send_transaction_to_l2(
     client: &hyper::Client<HttpConnector>,
     node_uri: &Uri,
-    wallet: &LocalWallet,
+    wallet: &TxSigner,
     to: Option<Address>,
     value: U256,
     calldata: Vec<u8>,
@@ -118,13 +87,10 @@ pub async fn send_transaction_to_l2(
         "eth_getTransactionCount",
         (wallet_addr, "latest"),
     )
-    .await
-    .expect("nonce");
+    .await?;
 
     let gas_price: U256 =
-        jsonrpc_request_client(RPC_REQUEST_TIMEOUT, client, node_uri, "eth_gasPrice", ())
-            .await
-            .expect("gasPrice");
+        jsonrpc_request_client(RPC_REQUEST_TIMEOUT, client, node_uri, "eth_gasPrice", ()).await?;
 
     let mut tx = TransactionRequest::new()
         .chain_id(wallet.chain_id())
@@ -140,20 +106,20 @@ pub async fn send_transaction_to_l2(
 
     let estimate: U256 = match gas_limit {
         Some(limit) => limit,
-        None => jsonrpc_request_client(
-            RPC_REQUEST_TIMEOUT,
-            client,
-            node_uri,
-            "eth_estimateGas",
-            [&tx],
-        )
-        .await
-        .expect("estimateGas"),
+        None => {
+            jsonrpc_request_client(
+                RPC_REQUEST_TIMEOUT,
+                client,
+                node_uri,
+                "eth_estimateGas",
+                [&tx],
+            )
+            .await?
+        }
     };
     let tx = tx.gas(estimate).into();
 
-    let sig = wallet.sign_transaction(&tx).await.unwrap();
-    let raw_tx = tx.rlp_signed(&sig);
+    let raw_tx = wallet.sign_transaction(&tx).await?;
 
     // TODO: will be obsolete once execution api is used
     jsonrpc_request_client(
@@ -223,8 +189,11 @@ pub fn format_block<T>(block: &Block<T>) -> String {
     )
 }
 
-pub async fn get_chain_head(client: &hyper::Client<HttpConnector>, uri: &Uri) -> BlockHeader {
-    let header: BlockHeader = jsonrpc_request_client(
+pub async fn get_chain_head(
+    client: &hyper::Client<HttpConnector>,
+    uri: &Uri,
+) -> Result<BlockHeader, String> {
+    jsonrpc_request_client(
         RPC_REQUEST_TIMEOUT,
         client,
         uri,
@@ -232,9 +201,6 @@ pub async fn get_chain_head(client: &hyper::Client<HttpConnector>, uri: &Uri) ->
         ["latest"],
     )
     .await
-    .unwrap();
-
-    header
 }
 
 pub async fn get_blocks_between(
@@ -242,7 +208,7 @@ pub async fn get_blocks_between(
     uri: &Uri,
     from: &H256,
     to: &H256,
-) -> Vec<Block<H256>> {
+) -> Result<Vec<Block<H256>>, String> {
     let mut ret: Vec<Block<H256>> = Vec::new();
     let mut hash = *to;
     loop {
@@ -253,8 +219,7 @@ pub async fn get_blocks_between(
             "eth_getBlockByHash",
             (hash, false),
         )
-        .await
-        .expect("eth_getBlockByHash");
+        .await?;
         hash = block.parent_hash;
 
         if block.hash.unwrap() != *from {
@@ -265,7 +230,7 @@ pub async fn get_blocks_between(
         }
     }
 
-    ret
+    Ok(ret)
 }
 
 /// encodes the proof from `eth_getCode` suitable for the PatriciaValidator contract.
@@ -365,3 +330,63 @@ pub fn encode_verifier_witness(
 
     Ok(witness)
 }
+
+/// Checks `block` against `max_gas`, `max_txs` and the circuit parameters
+/// that the prover uses for the gas used of `block`, see `CIRCUIT_CONFIGS`.
+/// `bytecode_len` is the size of the bytecodes executed in `block`.
+pub fn check_circuit_limits(
+    block: &Block<Transaction>,
+    bytecode_len: usize,
+    max_gas: u64,
+    max_txs: usize,
+) -> Result<(), String> {
+    let gas_used = block.gas_used.as_u64();
+    if gas_used > max_gas {
+        return Err(format!("gas used {} exceeds {}", gas_used, max_gas));
+    }
+
+    let config = CIRCUIT_CONFIGS
+        .iter()
+        .find(|e| gas_used <= e.block_gas_limit as u64)
+        .ok_or_else(|| format!("no circuit for gas used {}", gas_used))?;
+
+    let txs = block.transactions.len();
+    let max_txs = cmp::min(max_txs, config.max_txs);
+    if txs > max_txs {
+        return Err(format!("{} transactions exceed {}", txs, max_txs));
+    }
+
+    let calldata: usize = block.transactions.iter().map(|tx| tx.input.len()).sum();
+    if calldata > config.max_calldata {
+        return Err(format!(
+            "calldata size {} exceeds {}",
+            calldata, config.max_calldata
+        ));
+    }
+
+    if bytecode_len > config.max_bytecode {
+        return Err(format!(
+            "bytecode size {} exceeds {}",
+            bytecode_len, config.max_bytecode
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns `txs` without the transactions in `skipped` and the transactions
+/// of their senders that follow them, these can't be included without a nonce gap.
+pub fn skip_txs(txs: &[Transaction], skipped: &HashSet<H256>) -> Vec<Transaction> {
+    let mut senders = HashSet::new();
+
+    txs.iter()
+        .filter(|tx| {
+            if skipped.contains(&tx.hash) || senders.contains(&tx.from) {
+                senders.insert(tx.from);
+                return false;
+            }
+            true
+        })
+        .cloned()
+        .collect()
+}
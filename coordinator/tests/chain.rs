@@ -15,7 +15,6 @@ use ethers_core::types::H256;
 use ethers_core::types::U256;
 use ethers_core::types::U64;
 use ethers_core::utils::keccak256;
-use ethers_signers::Signer;
 use zkevm_common::json_rpc::jsonrpc_request;
 use zkevm_common::json_rpc::jsonrpc_request_client;
 use zkevm_common::prover::Proofs;
@@ -121,8 +120,8 @@ async fn native_withdraw() {
     .await
     .expect("eth_getBalance");
 
-    shared_state.sync().await;
-    shared_state.mine().await;
+    shared_state.sync().await.expect("sync");
+    shared_state.mine().await.expect("mine");
 
     {
         let mut tx_nonce: U256 = jsonrpc_request(
@@ -189,7 +188,7 @@ async fn native_withdraw() {
 
     // verify that all messages are picked up
     {
-        shared_state.sync().await;
+        shared_state.sync().await.expect("sync");
         for id in messages {
             let found = shared_state
                 .rw
@@ -353,7 +352,7 @@ async fn hop_cross_chain_message() {
             .transaction_to_l2(Some(hop), amount, calldata, None)
             .await
             .expect("tx_hash");
-        shared_state.mine().await;
+        shared_state.mine().await.expect("mine");
         wait_for_tx!(tx_hash, &shared_state.config.lock().await.l2_rpc_url);
     }
 
@@ -371,7 +370,7 @@ async fn hop_cross_chain_message() {
             .transaction_to_l2(Some(hop), U256::zero(), calldata, None)
             .await
             .expect("tx_hash_commit");
-        shared_state.mine().await;
+        shared_state.mine().await.expect("mine");
         wait_for_tx!(tx_hash_commit, &shared_state.config.lock().await.l2_rpc_url);
     }
 
@@ -471,7 +470,8 @@ async fn native_deposit_revert() {
                     calldata,
                     tx_nonce,
                 )
-                .await,
+                .await
+                .expect("sign_transaction_l1"),
             );
 
             tx_nonce = tx_nonce + 1;
@@ -539,7 +539,7 @@ async fn zero_eth_transfer() {
         )
         .await
         .expect("tx_hash");
-    shared_state.mine().await;
+    shared_state.mine().await.expect("mine");
     wait_for_tx!(tx_hash, &shared_state.config.lock().await.l2_rpc_url);
 
     finalize_chain!(shared_state);
@@ -557,7 +557,7 @@ async fn keccak() {
         .transaction_to_l2(None, U256::zero(), bytecode, None)
         .await
         .expect("tx_hash");
-    shared_state.mine().await;
+    shared_state.mine().await.expect("mine");
     shared_state.config.lock().await.dummy_prover = true;
     finalize_chain!(shared_state);
 
@@ -567,7 +567,7 @@ async fn keccak() {
         .transaction_to_l2(contract_addr, U256::zero(), vec![], None)
         .await
         .expect("tx_hash");
-    shared_state.mine().await;
+    shared_state.mine().await.expect("mine");
     wait_for_tx!(tx_hash, &shared_state.config.lock().await.l2_rpc_url);
     shared_state.config.lock().await.dummy_prover = false;
     shared_state.config.lock().await.mock_prover = true;
@@ -658,8 +658,55 @@ async fn l1_l2_sync_test() {
 async fn finalize_chain() {
     let shared_state = await_state!();
     sync!(shared_state);
-    shared_state.mine().await;
+    shared_state.mine().await.expect("mine");
+    finalize_chain!(shared_state);
+}
+
+// COORDINATOR_AGGREGATE_PROOF=true ./scripts/test_prover.sh --ignored finalize_blocks_batch
+#[ignore]
+#[tokio::test]
+async fn finalize_blocks_batch() {
+    let shared_state = await_state!();
     finalize_chain!(shared_state);
+
+    let (finalize_batch, aggregate_proof) = {
+        let mut config = shared_state.config.lock().await;
+        let prev = (config.finalize_batch, config.aggregate_proof);
+        config.finalize_batch = 2;
+        config.aggregate_proof = true;
+        prev
+    };
+    for _ in 0..2 {
+        let tx_hash = shared_state
+            .transaction_to_l2(
+                Some(shared_state.ro.l2_wallet.address()),
+                U256::zero(),
+                vec![],
+                None,
+            )
+            .await
+            .expect("tx_hash");
+        shared_state.mine().await.expect("mine");
+        wait_for_tx!(tx_hash, &shared_state.config.lock().await.l2_rpc_url);
+    }
+
+    let finalized = || {
+        shared_state
+            .ro
+            .metrics
+            .l1_gas_used
+            .with_label_values(&["finalizeBlocks"])
+            .get_sample_count()
+    };
+    let before = finalized();
+    finalize_chain!(shared_state);
+    {
+        let mut config = shared_state.config.lock().await;
+        config.finalize_batch = finalize_batch;
+        config.aggregate_proof = aggregate_proof;
+    }
+    // a rejected aggregated proof falls back to `finalizeBlock`
+    assert!(finalized() > before, "finalizeBlocks");
 }
 
 // ./scripts/test_prover.sh --ignored test_pi_commitment
@@ -678,7 +725,7 @@ async fn test_pi_commitment() {
         )
         .await
         .expect("tx_hash");
-    shared_state.mine().await;
+    shared_state.mine().await.expect("mine");
     wait_for_tx!(tx_hash, &shared_state.config.lock().await.l2_rpc_url);
 
     let tx_receipt: TransactionReceipt = shared_state
@@ -0,0 +1,100 @@
+use coordinator::utils::check_circuit_limits;
+use coordinator::utils::skip_txs;
+use ethers_core::types::{Address, Block, Bytes, Transaction, H256};
+use std::collections::HashSet;
+use zkevm_common::circuit_autogen::CIRCUIT_CONFIGS;
+
+fn block(gas_used: usize, txs: usize, calldata: usize) -> Block<Transaction> {
+    let tx = Transaction {
+        to: Some(Address::zero()),
+        input: Bytes::from(vec![1; calldata]),
+        ..Default::default()
+    };
+
+    Block {
+        gas_used: gas_used.into(),
+        transactions: vec![tx; txs],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn circuit_limits() {
+    let smallest = &CIRCUIT_CONFIGS[0];
+    let largest = CIRCUIT_CONFIGS.last().unwrap();
+    let max_gas = largest.block_gas_limit as u64;
+
+    check_circuit_limits(&block(0, 0, 0), 0, max_gas, largest.max_txs).expect("empty block");
+    check_circuit_limits(
+        &block(smallest.block_gas_limit, smallest.max_txs, 0),
+        smallest.max_bytecode,
+        max_gas,
+        largest.max_txs,
+    )
+    .expect("smallest circuit");
+
+    // the circuit is selected by the gas used
+    check_circuit_limits(
+        &block(smallest.block_gas_limit, smallest.max_txs + 1, 0),
+        0,
+        max_gas,
+        largest.max_txs,
+    )
+    .expect_err("transactions");
+    check_circuit_limits(
+        &block(smallest.block_gas_limit, 1, smallest.max_calldata + 1),
+        0,
+        max_gas,
+        largest.max_txs,
+    )
+    .expect_err("calldata");
+    check_circuit_limits(
+        &block(smallest.block_gas_limit, 1, 0),
+        smallest.max_bytecode + 1,
+        max_gas,
+        largest.max_txs,
+    )
+    .expect_err("bytecode");
+    check_circuit_limits(
+        &block(largest.block_gas_limit + 1, 1, 0),
+        0,
+        u64::MAX,
+        largest.max_txs,
+    )
+    .expect_err("no circuit");
+
+    // the limits of the config
+    check_circuit_limits(&block(1000, 1, 0), 0, 999, largest.max_txs).expect_err("max gas");
+    check_circuit_limits(&block(1000, 2, 0), 0, max_gas, 1).expect_err("max txs");
+}
+
+#[test]
+fn skip_unprovable_txs() {
+    let tx = |hash: u8, from: u8| Transaction {
+        hash: H256::repeat_byte(hash),
+        from: Address::repeat_byte(from),
+        ..Default::default()
+    };
+    let txs = vec![tx(1, 1), tx(2, 2), tx(3, 1), tx(4, 3)];
+    let hashes = |txs: Vec<Transaction>| txs.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+
+    assert_eq!(hashes(skip_txs(&txs, &HashSet::new())), hashes(txs.clone()));
+
+    // the following transactions of the same sender are skipped as well
+    let skipped = HashSet::from([H256::repeat_byte(1)]);
+    assert_eq!(
+        hashes(skip_txs(&txs, &skipped)),
+        vec![H256::repeat_byte(2), H256::repeat_byte(4)]
+    );
+
+    // but not the preceding ones
+    let skipped = HashSet::from([H256::repeat_byte(3)]);
+    assert_eq!(
+        hashes(skip_txs(&txs, &skipped)),
+        vec![
+            H256::repeat_byte(1),
+            H256::repeat_byte(2),
+            H256::repeat_byte(4)
+        ]
+    );
+}
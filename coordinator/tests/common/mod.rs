@@ -136,10 +136,10 @@ macro_rules! await_state {
 macro_rules! sync {
     ($shared_state:expr) => {
         // sync bridge and process events
-        $shared_state.sync().await;
+        $shared_state.sync().await.expect("sync");
         while $shared_state.rw.lock().await.l1_message_queue.len() > 0 {
-            $shared_state.mine().await;
-            $shared_state.sync().await;
+            $shared_state.mine().await.expect("mine");
+            $shared_state.sync().await.expect("sync");
             sleep!(300);
         }
     };
@@ -194,14 +194,14 @@ macro_rules! finalize_chain {
             drop(rw);
 
             sync!($shared_state);
-            $shared_state.submit_blocks().await;
+            $shared_state.submit_blocks().await.expect("submit_blocks");
             $shared_state
                 .finalize_blocks()
                 .await
                 .expect("finalize_blocks");
             sync!($shared_state);
             while $shared_state.rw.lock().await.l2_message_queue.len() != 0 {
-                $shared_state.relay_to_l1().await;
+                $shared_state.relay_to_l1().await.expect("relay_to_l1");
                 sync!($shared_state);
             }
         }
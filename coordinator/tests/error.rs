@@ -0,0 +1,31 @@
+use coordinator::error::CoordinatorError;
+use zkevm_common::json_rpc::JsonRpcError;
+use zkevm_common::json_rpc::JsonRpcRequestError;
+
+fn response(code: i32, message: &str) -> JsonRpcRequestError {
+    JsonRpcRequestError::Response(JsonRpcError {
+        code,
+        message: message.to_string(),
+    })
+}
+
+fn classify(err: JsonRpcRequestError) -> CoordinatorError {
+    err.into()
+}
+
+#[test]
+fn request_error_classification() {
+    assert_eq!(
+        classify(response(3, "execution reverted: nonce")),
+        CoordinatorError::Revert("execution reverted: nonce".to_string())
+    );
+    assert_eq!(
+        classify(response(-32000, "execution reverted")),
+        CoordinatorError::Revert("execution reverted".to_string())
+    );
+
+    assert!(classify(response(-32000, "header not found")).is_transient());
+    assert!(classify(response(-32005, "request rate exceeded")).is_transient());
+    assert!(classify(JsonRpcRequestError::NoResult).is_transient());
+    assert!(classify(JsonRpcRequestError::Transport("timeout".to_string())).is_transient());
+}
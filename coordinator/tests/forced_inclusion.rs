@@ -0,0 +1,108 @@
+use coordinator::shared_state::RwState;
+use coordinator::structs::DropReason;
+use coordinator::structs::DroppedMessage;
+use coordinator::structs::MessageBeacon;
+use coordinator::structs::MessageStatus;
+use ethers_core::types::{Address, H256, U256, U64};
+
+fn message(id: u8) -> MessageBeacon {
+    MessageBeacon {
+        id: H256::repeat_byte(id),
+        from: Address::zero(),
+        to: Address::zero(),
+        value: U256::zero(),
+        fee: U256::zero(),
+        deadline: U256::MAX,
+        nonce: U256::from(id),
+        calldata: vec![],
+    }
+}
+
+#[test]
+fn forced_inclusion() {
+    let mut rw = RwState::default();
+    for id in 1..=3 {
+        rw.l1_message_queue.push_back(message(id));
+    }
+    rw.l1_message_deadlines.insert(message(1).id, 300);
+    rw.l1_message_deadlines.insert(message(2).id, 200);
+
+    // messages without a deadline are due now and go first
+    assert_eq!(rw.order_l1_message_queue(100), 3);
+    let ids: Vec<H256> = rw.l1_message_queue.iter().map(|e| e.id).collect();
+    assert_eq!(ids, vec![message(3).id, message(2).id, message(1).id]);
+    assert_eq!(rw.l1_message_deadlines[&message(3).id], 100);
+
+    // pool transactions wait while any message is overdue
+    assert_eq!(rw.overdue_l1_messages(99), 0);
+    assert_eq!(rw.overdue_l1_messages(100), 1);
+    assert_eq!(rw.overdue_l1_messages(250), 2);
+
+    // until the overdue messages are delivered
+    rw.remove_l1_messages(&[message(3).id, message(2).id]);
+    assert_eq!(rw.overdue_l1_messages(250), 0);
+    assert_eq!(rw.overdue_l1_messages(300), 1);
+    assert!(!rw.l1_message_deadlines.contains_key(&message(2).id));
+}
+
+#[test]
+fn drop_reasons() {
+    // the block is full, the message waits for the next block
+    assert_eq!(
+        DropReason::from_block_error("gas limit reached".to_string(), false),
+        None
+    );
+    // not even an otherwise empty block fits the message
+    assert_eq!(
+        DropReason::from_block_error("gas limit reached".to_string(), true),
+        Some(DropReason::TooLarge)
+    );
+    assert_eq!(
+        DropReason::from_block_error("execution reverted".to_string(), false),
+        Some(DropReason::BlockFailed("execution reverted".to_string()))
+    );
+}
+
+#[test]
+fn drop_recording() {
+    let mut rw = RwState::default();
+    rw.l1_message_queue.push_back(message(1));
+    rw.l1_message_queue.push_back(message(2));
+    rw.order_l1_message_queue(100);
+
+    rw.remove_l1_messages(&[message(1).id]);
+    rw.record_dropped_messages([DroppedMessage {
+        id: message(1).id,
+        reason: DropReason::TooLarge,
+        block_number: U64::from(7),
+        timestamp: 100,
+    }]);
+
+    assert_eq!(rw.l1_message_queue.len(), 1);
+    assert_eq!(
+        rw.message_status(message(1).id).status,
+        MessageStatus::Dropped {
+            reason: DropReason::TooLarge,
+            block_number: U64::from(7),
+        }
+    );
+    assert!(matches!(
+        rw.message_status(message(2).id).status,
+        MessageStatus::Queued { .. }
+    ));
+
+    // only the latest drops are kept
+    for i in 0..2000u64 {
+        rw.record_dropped_messages([DroppedMessage {
+            id: H256::from_low_u64_be(i),
+            reason: DropReason::DeadlineExceeded,
+            block_number: U64::from(i),
+            timestamp: i,
+        }]);
+    }
+    assert!(rw.dropped_messages.len() < 2000);
+    assert_eq!(
+        rw.dropped_messages.back().unwrap().id,
+        H256::from_low_u64_be(1999)
+    );
+}
@@ -0,0 +1,76 @@
+use coordinator::shared_state::RwState;
+use coordinator::structs::L1BlockRecord;
+use coordinator::structs::MessageBeacon;
+use ethers_core::types::{Address, H256, U256, U64};
+
+fn message(id: u8) -> MessageBeacon {
+    MessageBeacon {
+        id: H256::repeat_byte(id),
+        from: Address::zero(),
+        to: Address::zero(),
+        value: U256::zero(),
+        fee: U256::zero(),
+        deadline: U256::zero(),
+        nonce: U256::from(id),
+        calldata: vec![],
+    }
+}
+
+#[test]
+fn l1_reorg_rollback() {
+    let mut rw = RwState::default();
+    let genesis = H256::repeat_byte(0xf0);
+    rw.chain_state.safe_block_hash = genesis;
+    rw.chain_state.finalized_block_hash = genesis;
+
+    // block 10: a message and a block submission
+    let mut record = L1BlockRecord::new(U64::from(10), H256::repeat_byte(10));
+    record.l1_messages.push(message(1).id);
+    record.prev_safe_block_hash = Some(genesis);
+    rw.l1_message_queue.push_back(message(1));
+    rw.chain_state.safe_block_hash = H256::repeat_byte(0xa1);
+    rw.l1_blocks.push_back(record);
+
+    // block 12: another message, a block submission and a finalization
+    let mut record = L1BlockRecord::new(U64::from(12), H256::repeat_byte(12));
+    record.l1_messages.push(message(2).id);
+    record.l1_delivered_messages.push(H256::repeat_byte(0xd1));
+    record.l2_messages.push(message(3).id);
+    record.prev_safe_block_hash = Some(rw.chain_state.safe_block_hash);
+    record.prev_finalized_block_hash = Some(genesis);
+    rw.l1_message_queue.push_back(message(2));
+    rw.l1_delivered_messages.push(H256::repeat_byte(0xd1));
+    rw.l2_message_queue.push(message(3));
+    rw.chain_state.safe_block_hash = H256::repeat_byte(0xa2);
+    rw.chain_state.finalized_block_hash = H256::repeat_byte(0xa1);
+    rw.l1_blocks.push_back(record);
+
+    // a submission that `sync` didn't see yet
+    rw.pending_submissions.insert(H256::repeat_byte(0xa3));
+    rw.pending_finalizations.insert(H256::repeat_byte(0xa2));
+
+    // block 15: the last synced block without events
+    rw.l1_blocks
+        .push_back(L1BlockRecord::new(U64::from(15), H256::repeat_byte(15)));
+    rw.l1_last_sync_block = U64::from(15);
+
+    // blocks after 10 are reorged
+    rw.rollback_l1(U64::from(10));
+
+    assert_eq!(rw.l1_last_sync_block, U64::from(10));
+    assert_eq!(rw.l1_blocks.len(), 1);
+    assert_eq!(rw.chain_state.safe_block_hash, H256::repeat_byte(0xa1));
+    assert_eq!(rw.chain_state.finalized_block_hash, genesis);
+    assert_eq!(rw.l1_message_queue.len(), 1);
+    assert_eq!(rw.l1_message_queue[0].id, message(1).id);
+    assert!(rw.l1_delivered_messages.is_empty());
+    assert!(rw.l2_message_queue.is_empty());
+    // the blocks after the restored hashes are submitted and finalized again
+    assert!(rw.pending_submissions.is_empty());
+    assert!(rw.pending_finalizations.is_empty());
+
+    // a rollback to a later block doesn't change anything
+    rw.rollback_l1(U64::from(11));
+    assert_eq!(rw.l1_last_sync_block, U64::from(10));
+    assert_eq!(rw.l1_blocks.len(), 1);
+}
@@ -0,0 +1,171 @@
+use coordinator::log_fetcher::LogFetcher;
+use coordinator::log_fetcher::LIMIT_RECOVERY;
+use ethers_core::types::{Filter, U64};
+use hyper::client::HttpConnector;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Client, Request, Response, Server, Uri};
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// The maximum block range the stub node accepts.
+const RANGE_LIMIT: u64 = 4;
+/// The only block with a log.
+const LOG_BLOCK: u64 = 10;
+/// The first request starting at this block is rate limited.
+const RATE_LIMITED_BLOCK: u64 = 5;
+
+#[derive(Default)]
+struct StubState {
+    /// The number of requests rejected because of the range limit.
+    range_errors: AtomicU64,
+    /// The number of requests starting at `RATE_LIMITED_BLOCK`.
+    rate_limit_requests: AtomicU64,
+}
+
+/// A json-rpc node that only serves `eth_getLogs` with a block range limit.
+async fn stub_node(
+    state: Arc<StubState>,
+    req: Request<Body>,
+) -> Result<Response<Body>, hyper::Error> {
+    let body = hyper::body::to_bytes(req.into_body()).await?;
+    let req: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let filter = &req["params"][0];
+    let block = |key: &str| U64::from_str_radix(filter[key].as_str().unwrap(), 16).unwrap();
+    let from = block("fromBlock").as_u64();
+    let to = block("toBlock").as_u64();
+
+    let rate_limited =
+        from == RATE_LIMITED_BLOCK && state.rate_limit_requests.fetch_add(1, Ordering::SeqCst) == 0;
+    let resp = if rate_limited {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": req["id"],
+            "error": { "code": -32005, "message": "project ID request rate exceeded" },
+        })
+    } else if to - from + 1 > RANGE_LIMIT {
+        state.range_errors.fetch_add(1, Ordering::SeqCst);
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": req["id"],
+            "error": { "code": -32005, "message": "block range too large" },
+        })
+    } else {
+        let logs: Vec<serde_json::Value> = (from..=to)
+            .filter(|e| *e == LOG_BLOCK)
+            .map(|e| {
+                serde_json::json!({
+                    "address": "0x0000000000000000000000000000000000000000",
+                    "topics": [],
+                    "data": "0x",
+                    "blockNumber": format!("{:#x}", e),
+                })
+            })
+            .collect();
+        serde_json::json!({ "jsonrpc": "2.0", "id": req["id"], "result": logs })
+    };
+
+    Ok(Response::new(Body::from(resp.to_string())))
+}
+
+/// Spawns a stub node and returns its uri.
+fn spawn_stub_node(state: Arc<StubState>) -> Uri {
+    let service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move { Ok::<_, hyper::Error>(service_fn(move |req| stub_node(state.clone(), req))) }
+    });
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(service);
+    let uri = Uri::try_from(format!("http://{}", server.local_addr())).unwrap();
+    tokio::spawn(server);
+
+    uri
+}
+
+/// Fetches the logs of the blocks `from..=latest`.
+async fn fetch_all(
+    fetcher: &LogFetcher,
+    client: &Client<HttpConnector>,
+    uri: &Uri,
+    mut from: U64,
+    latest: U64,
+) {
+    while let Some(range) = fetcher
+        .next(client, uri, &Filter::new(), from, latest)
+        .await
+        .expect("next")
+    {
+        from = range.to + 1u64;
+    }
+}
+
+#[tokio::test]
+async fn log_fetcher_adaptive_window() {
+    let state = Arc::new(StubState::default());
+    let uri = spawn_stub_node(state.clone());
+
+    let client = hyper::Client::new();
+    let filter = Filter::new();
+    let latest = U64::from(20);
+    let fetcher = LogFetcher::new(64, 0);
+    let mut from = U64::from(1);
+    let mut synced = U64::zero();
+    let mut logs = 0;
+    while let Some(range) = fetcher
+        .next(&client, &uri, &filter, from, latest)
+        .await
+        .expect("next")
+    {
+        assert_eq!(range.from, from);
+        assert!((range.to - range.from).as_u64() < RANGE_LIMIT);
+        logs += range.logs.len();
+        synced = range.synced;
+        from = range.to + 1u64;
+    }
+
+    assert_eq!(logs, 1);
+    assert_eq!(from, latest + 1u64);
+    // the latest block has no logs yet and is fetched again
+    assert_eq!(synced, latest - 1u64);
+    // stops growing once the range limit was hit
+    assert!(fetcher.window() <= RANGE_LIMIT);
+    // the rate limited request was retried
+    assert_eq!(state.rate_limit_requests.load(Ordering::SeqCst), 2);
+
+    // other errors don't shrink the window
+    let window = fetcher.window();
+    let unreachable = Uri::from_static("http://127.0.0.1:1");
+    assert!(fetcher
+        .next(&client, &unreachable, &filter, U64::from(1), latest)
+        .await
+        .is_err());
+    assert_eq!(fetcher.window(), window);
+
+    // blocks within the confirmation depth are not fetched
+    let fetcher = LogFetcher::new(64, 5);
+    assert!(fetcher
+        .next(&client, &uri, &filter, U64::from(16), latest)
+        .await
+        .expect("next")
+        .is_none());
+}
+
+#[tokio::test]
+async fn log_fetcher_limit_recovery() {
+    let state = Arc::new(StubState::default());
+    let uri = spawn_stub_node(state.clone());
+    let client = hyper::Client::new();
+    let fetcher = LogFetcher::new(64, 0);
+
+    // shrinks to the range limit and stays there for `LIMIT_RECOVERY - 1` requests
+    let latest = U64::from((LIMIT_RECOVERY - 1) * RANGE_LIMIT);
+    fetch_all(&fetcher, &client, &uri, U64::from(1), latest).await;
+    assert_eq!(fetcher.window(), RANGE_LIMIT);
+    let range_errors = state.range_errors.load(Ordering::SeqCst);
+
+    // tries a larger window once more after `LIMIT_RECOVERY` requests at the limit
+    let from = latest + 1u64;
+    fetch_all(&fetcher, &client, &uri, from, from + 4 * RANGE_LIMIT - 1u64).await;
+    assert_eq!(state.range_errors.load(Ordering::SeqCst), range_errors + 1);
+    assert_eq!(fetcher.window(), RANGE_LIMIT);
+}
@@ -0,0 +1,111 @@
+use coordinator::shared_state::RwState;
+use coordinator::structs::DropReason;
+use coordinator::structs::DroppedMessage;
+use coordinator::structs::MessageBeacon;
+use coordinator::structs::MessageDirection;
+use coordinator::structs::MessageStatus;
+use ethers_core::types::{Address, H256, U256, U64};
+
+fn message(id: u8) -> MessageBeacon {
+    MessageBeacon {
+        id: H256::repeat_byte(id),
+        from: Address::zero(),
+        to: Address::zero(),
+        value: U256::zero(),
+        fee: U256::zero(),
+        deadline: U256::MAX,
+        nonce: U256::from(id),
+        calldata: vec![],
+    }
+}
+
+#[test]
+fn message_status() {
+    let mut rw = RwState::default();
+    let queued = message(1);
+    let delivered = message(2);
+    let dropped = message(3);
+    let relayed = message(4);
+
+    rw.l1_message_queue.push_back(queued.clone());
+    rw.l1_message_deadlines.insert(queued.id, 100);
+    rw.record_message_dispatch(
+        queued.id,
+        MessageDirection::L1ToL2,
+        Some(H256::repeat_byte(11)),
+        10,
+    );
+
+    rw.record_message_dispatch(
+        delivered.id,
+        MessageDirection::L1ToL2,
+        Some(H256::repeat_byte(12)),
+        20,
+    );
+    rw.record_message_delivery(
+        delivered.id,
+        MessageDirection::L1ToL2,
+        Some(H256::repeat_byte(13)),
+        21,
+    );
+    rw.l2_delivered_messages.push(delivered.id);
+
+    rw.dropped_messages.push_back(DroppedMessage {
+        id: dropped.id,
+        reason: DropReason::DeadlineExceeded,
+        block_number: U64::from(7),
+        timestamp: 30,
+    });
+
+    rw.l2_message_queue.push(relayed.clone());
+
+    let status = rw.message_status(queued.id);
+    assert_eq!(
+        status.status,
+        MessageStatus::Queued {
+            inclusion_deadline: Some(100)
+        }
+    );
+    let record = status.record.expect("record");
+    assert_eq!(record.dispatch_tx, Some(H256::repeat_byte(11)));
+    assert_eq!(record.dispatched_at, Some(10));
+    assert_eq!(record.delivery_tx, None);
+
+    let status = rw.message_status(delivered.id);
+    assert_eq!(status.status, MessageStatus::Delivered);
+    let record = status.record.expect("record");
+    assert_eq!(record.dispatch_tx, Some(H256::repeat_byte(12)));
+    assert_eq!(record.delivery_tx, Some(H256::repeat_byte(13)));
+    assert_eq!(record.delivered_at, Some(21));
+
+    assert_eq!(
+        rw.message_status(dropped.id).status,
+        MessageStatus::Dropped {
+            reason: DropReason::DeadlineExceeded,
+            block_number: U64::from(7),
+        }
+    );
+    assert_eq!(
+        rw.message_status(relayed.id).status,
+        MessageStatus::Queued {
+            inclusion_deadline: None
+        }
+    );
+    assert_eq!(
+        rw.message_status(H256::repeat_byte(5)).status,
+        MessageStatus::Unknown
+    );
+
+    let pending = rw.pending_messages(MessageDirection::L1ToL2);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, queued.id);
+    let pending = rw.pending_messages(MessageDirection::L2ToL1);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, relayed.id);
+
+    // the json format of the rpc methods
+    let json = serde_json::to_value(rw.message_status(queued.id)).unwrap();
+    assert_eq!(json["status"]["type"], "queued");
+    assert_eq!(json["status"]["inclusionDeadline"], 100);
+    assert_eq!(json["record"]["direction"], "l1ToL2");
+}
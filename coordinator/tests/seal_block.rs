@@ -0,0 +1,102 @@
+use clap::Parser;
+use coordinator::config::Config;
+use zkevm_common::circuit_autogen::CIRCUIT_CONFIGS;
+use zkevm_common::prover::MAX_AGGREGATION_BLOCKS;
+
+fn try_config(args: &[&str]) -> Result<Config, clap::Error> {
+    let required = [
+        "coordinator",
+        "--rpc-server-nodes",
+        "localhost:8545",
+        "--listen",
+        "127.0.0.1:0",
+        "--l1-rpc-url",
+        "http://localhost:8545",
+        "--l1-bridge",
+        "0x0000000000000000000000000000000000000000",
+        "--l1-priv",
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "--l2-rpc-url",
+        "http://localhost:8545",
+        "--prover-rpcd-url",
+        "http://localhost:8545",
+        "--circuit-name",
+        "pi",
+    ];
+
+    Config::try_parse_from(required.iter().chain(args.iter()))
+}
+
+fn config(args: &[&str]) -> Config {
+    try_config(args).expect("config")
+}
+
+#[test]
+fn seal_block() {
+    let config = config(&["--block-interval", "3", "--min-block-txs", "2"]);
+
+    // waits for the block interval
+    assert!(!config.seal_block(2, 10));
+    assert!(config.seal_block(3, 10));
+    // and for enough transactions
+    assert!(!config.seal_block(3, 1));
+    assert!(config.seal_block(3, 2));
+    // no empty blocks without `empty_block_interval`
+    assert!(!config.seal_block(u64::MAX, 0));
+    assert!(!config.heartbeat_due(u64::MAX));
+}
+
+#[test]
+fn seal_block_heartbeat() {
+    let config = config(&[
+        "--block-interval",
+        "3",
+        "--min-block-txs",
+        "2",
+        "--empty-block-interval",
+        "10",
+    ]);
+
+    assert!(!config.heartbeat_due(9));
+    assert!(!config.seal_block(9, 1));
+    assert!(config.heartbeat_due(10));
+    assert!(config.seal_block(10, 0));
+    assert!(config.seal_block(10, 1));
+}
+
+#[test]
+fn block_limits() {
+    let largest = CIRCUIT_CONFIGS.last().unwrap();
+    let max_gas = largest.block_gas_limit as u64;
+
+    assert_eq!(config(&[]).block_limits(), (max_gas, largest.max_txs));
+    assert_eq!(
+        config(&["--max-block-gas", "1000", "--max-block-txs", "1"]).block_limits(),
+        (1000, 1)
+    );
+    // bound by the largest circuit
+    assert_eq!(
+        config(&[
+            "--max-block-gas",
+            &(max_gas + 1).to_string(),
+            "--max-block-txs",
+            &(largest.max_txs + 1).to_string(),
+        ])
+        .block_limits(),
+        (max_gas, largest.max_txs)
+    );
+}
+
+#[test]
+fn finalize_batch_limit() {
+    assert_eq!(config(&[]).finalize_batch, 1);
+    let max = MAX_AGGREGATION_BLOCKS.to_string();
+    assert_eq!(
+        config(&["--finalize-batch", &max]).finalize_batch,
+        MAX_AGGREGATION_BLOCKS
+    );
+    // no aggregation verifier for these sizes
+    let over = (MAX_AGGREGATION_BLOCKS + 1).to_string();
+    assert!(try_config(&["--finalize-batch", &over]).is_err());
+    assert!(try_config(&["--finalize-batch", "0"]).is_err());
+}
@@ -0,0 +1,88 @@
+use coordinator::signer::TxSigner;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::{Address, Eip1559TransactionRequest, U256};
+use ethers_signers::{LocalWallet, Signer};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use std::net::SocketAddr;
+
+const CHAIN_ID: u64 = 99;
+
+fn wallet() -> LocalWallet {
+    "0x2bbf6c1f5a7e3e4c23df4aaa59ab6b5a7fc3b4a3a1fa1a0e0b73c0b2a6b0f5c1"
+        .parse::<LocalWallet>()
+        .unwrap()
+        .with_chain_id(CHAIN_ID)
+}
+
+/// An external signer that signs with `wallet` and responds like geth or clef.
+async fn stub_signer(req: Request<Body>) -> Result<Response<Body>, hyper::Error> {
+    let body = hyper::body::to_bytes(req.into_body()).await?;
+    let req: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let wallet = wallet();
+
+    let result = match req["method"].as_str().unwrap() {
+        "eth_accounts" => serde_json::json!([wallet.address()]),
+        "eth_signTransaction" => {
+            let tx: TypedTransaction = serde_json::from_value(req["params"][0].clone()).unwrap();
+            assert_eq!(tx.from(), Some(&wallet.address()));
+            let sig = wallet.sign_transaction(&tx).await.unwrap();
+            serde_json::json!({ "raw": tx.rlp_signed(&sig), "tx": tx })
+        }
+        method => panic!("unexpected method {}", method),
+    };
+    let resp = serde_json::json!({ "jsonrpc": "2.0", "id": req["id"], "result": result });
+
+    Ok(Response::new(Body::from(resp.to_string())))
+}
+
+#[tokio::test]
+async fn signer_remote() {
+    let service = make_service_fn(|_| async { Ok::<_, hyper::Error>(service_fn(stub_signer)) });
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(service);
+    let spec = format!("remote:http://{}", server.local_addr());
+    tokio::spawn(server);
+
+    let local = TxSigner::from(wallet());
+    let remote = TxSigner::from_spec(&spec, CHAIN_ID, None)
+        .await
+        .expect("remote signer");
+    assert_eq!(remote.address(), local.address());
+    assert_eq!(remote.chain_id(), CHAIN_ID);
+
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .to(Address::zero())
+        .nonce(1u64)
+        .value(U256::one())
+        .gas(21000u64)
+        .max_priority_fee_per_gas(1u64)
+        .max_fee_per_gas(1000u64)
+        .chain_id(CHAIN_ID)
+        .into();
+    // the signatures are deterministic
+    assert_eq!(
+        remote.sign_transaction(&tx).await.expect("remote"),
+        local.sign_transaction(&tx).await.expect("local")
+    );
+}
+
+#[tokio::test]
+async fn signer_keystore() {
+    let dir = std::env::temp_dir().join(format!("coordinator-keystore-{}", rand::random::<u64>()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (wallet, name) =
+        LocalWallet::new_keystore(&dir, &mut rand::thread_rng(), "password", None).unwrap();
+    let spec = format!("keystore:{}", dir.join(name).to_str().unwrap());
+
+    let signer = TxSigner::from_spec(&spec, CHAIN_ID, Some("password"))
+        .await
+        .expect("keystore signer");
+    assert_eq!(signer.address(), wallet.address());
+    assert_eq!(signer.chain_id(), CHAIN_ID);
+    assert!(TxSigner::from_spec(&spec, CHAIN_ID, Some("wrong"))
+        .await
+        .is_err());
+    assert!(TxSigner::from_spec(&spec, CHAIN_ID, None).await.is_err());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,96 @@
+use coordinator::shared_state::RwState;
+use coordinator::shared_state::DELIVERED_MESSAGES_HISTORY;
+use coordinator::state_store::StateStore;
+use coordinator::structs::DropReason;
+use coordinator::structs::DroppedMessage;
+use coordinator::structs::MessageBeacon;
+use ethers_core::types::{Address, H256, U256, U64};
+
+#[test]
+fn state_store_roundtrip() {
+    let data_dir =
+        std::env::temp_dir().join(format!("coordinator-state-store-{}", rand::random::<u64>()));
+    let data_dir = data_dir.to_str().unwrap();
+    let state_store = StateStore::new(data_dir).expect("state store");
+    assert!(state_store.load().expect("load").is_none());
+
+    let msg = MessageBeacon {
+        id: H256::repeat_byte(1),
+        from: Address::repeat_byte(2),
+        to: Address::repeat_byte(3),
+        value: U256::from(4),
+        fee: U256::from(5),
+        deadline: U256::from(6),
+        nonce: U256::from(7),
+        calldata: vec![8, 9],
+    };
+    let mut rw = RwState::default();
+    rw.chain_state.head_block_hash = H256::repeat_byte(10);
+    rw.l1_last_sync_block = U64::from(11);
+    rw.l2_last_sync_block = U64::from(12);
+    rw.l1_message_queue.push_back(msg.clone());
+    rw.l2_message_queue.push(msg.clone());
+    rw.l1_delivered_messages.push(msg.id);
+    rw.l1_message_deadlines.insert(msg.id, 13);
+    rw.dropped_messages.push_back(DroppedMessage {
+        id: msg.id,
+        reason: DropReason::SimulationFailed("revert".to_string()),
+        block_number: U64::from(14),
+        timestamp: 15,
+    });
+    state_store.save(&rw.snapshot()).expect("save");
+
+    let mut restored = RwState::default();
+    restored.restore(state_store.load().expect("load").expect("snapshot"));
+    assert_eq!(restored.chain_state.head_block_hash, H256::repeat_byte(10));
+    assert_eq!(restored.l1_last_sync_block, U64::from(11));
+    assert_eq!(restored.l2_last_sync_block, U64::from(12));
+    assert_eq!(restored.l1_message_queue[0].calldata, msg.calldata);
+    assert_eq!(restored.l2_message_queue[0].id, msg.id);
+    assert_eq!(restored.l1_delivered_messages, vec![msg.id]);
+    assert!(restored.l2_delivered_messages.is_empty());
+    assert_eq!(restored.l1_message_deadlines[&msg.id], 13);
+    assert_eq!(
+        restored.dropped_messages[0].reason,
+        DropReason::SimulationFailed("revert".to_string())
+    );
+    assert_eq!(restored.dropped_messages[0].block_number, U64::from(14));
+
+    // unchanged snapshots are not written again
+    let path = std::path::Path::new(data_dir).join("state.json");
+    std::fs::remove_file(&path).expect("remove");
+    state_store.save(&rw.snapshot()).expect("save");
+    assert!(!path.exists());
+    rw.l1_last_sync_block = U64::from(16);
+    state_store.save(&rw.snapshot()).expect("save");
+    assert!(path.exists());
+
+    // snapshots of other schema versions are rejected
+    let mut snapshot = rw.snapshot();
+    snapshot.version += 1;
+    state_store.save(&snapshot).expect("save");
+    assert!(state_store.load().is_err());
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn delivered_messages_history() {
+    let ids: Vec<H256> = (0..DELIVERED_MESSAGES_HISTORY as u64 + 10)
+        .map(H256::from_low_u64_be)
+        .collect();
+    let mut rw = RwState::default();
+    rw.record_l1_delivered_messages(&ids[..10]);
+    rw.record_l1_delivered_messages(&ids[10..]);
+    rw.record_l2_delivered_messages(&ids);
+
+    // keeps the latest ones
+    assert_eq!(rw.l1_delivered_messages, ids[10..]);
+    assert_eq!(rw.l2_delivered_messages, ids[10..]);
+
+    let mut snapshot = rw.snapshot();
+    snapshot.l1_delivered_messages = ids.clone();
+    let mut restored = RwState::default();
+    restored.restore(snapshot);
+    assert_eq!(restored.l1_delivered_messages, ids[10..]);
+}
@@ -0,0 +1,168 @@
+use coordinator::tx_manager::bump_fees;
+use coordinator::tx_manager::TxError;
+use coordinator::tx_manager::TxManager;
+use coordinator::tx_manager::FEE_BUMP_PERCENT;
+use ethers_core::types::{Address, Bytes, Eip1559TransactionRequest, H256, U256};
+use ethers_core::utils::keccak256;
+use ethers_signers::{LocalWallet, Signer};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, Uri};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const RECEIPT_BLOCK: u64 = 16;
+
+/// The broadcasted raw transactions and the number of gas estimations.
+#[derive(Default)]
+struct StubState {
+    sent: Vec<Bytes>,
+    estimates: usize,
+}
+
+/// A json-rpc node that only includes the `included`th broadcasted transaction.
+async fn stub_node(
+    req: Request<Body>,
+    state: Arc<Mutex<StubState>>,
+    included: usize,
+) -> Result<Response<Body>, hyper::Error> {
+    let body = hyper::body::to_bytes(req.into_body()).await?;
+    let req: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let params = &req["params"];
+
+    let result = match req["method"].as_str().unwrap() {
+        "eth_getTransactionCount" => serde_json::json!("0x0"),
+        "eth_gasPrice" => serde_json::json!("0x3b9aca00"),
+        "eth_maxPriorityFeePerGas" => serde_json::json!("0x1"),
+        "eth_createAccessList" => serde_json::json!({ "accessList": [], "gasUsed": "0x5208" }),
+        "eth_estimateGas" => {
+            state.lock().unwrap().estimates += 1;
+            serde_json::json!("0x5208")
+        }
+        "eth_blockNumber" => serde_json::json!(format!("{:#x}", RECEIPT_BLOCK + 2)),
+        "eth_sendRawTransaction" => {
+            let raw_tx: Bytes = serde_json::from_value(params[0].clone()).unwrap();
+            let tx_hash = H256::from(keccak256(&raw_tx));
+            state.lock().unwrap().sent.push(raw_tx);
+            serde_json::json!(tx_hash)
+        }
+        "eth_getTransactionReceipt" => {
+            let tx_hash: H256 = serde_json::from_value(params[0].clone()).unwrap();
+            let state = state.lock().unwrap();
+            let included = state.sent.get(included).map(|e| H256::from(keccak256(e)));
+            if included == Some(tx_hash) {
+                serde_json::json!({
+                    "transactionHash": tx_hash,
+                    "transactionIndex": "0x0",
+                    "blockHash": H256::repeat_byte(1),
+                    "blockNumber": format!("{:#x}", RECEIPT_BLOCK),
+                    "from": Address::zero(),
+                    "to": Address::zero(),
+                    "cumulativeGasUsed": "0x5208",
+                    "gasUsed": "0x5208",
+                    "contractAddress": null,
+                    "logs": [],
+                    "logsBloom": format!("0x{}", "00".repeat(256)),
+                    "status": "0x1",
+                    "type": "0x2",
+                    "effectiveGasPrice": "0x3b9aca00",
+                })
+            } else {
+                serde_json::Value::Null
+            }
+        }
+        method => panic!("unexpected method {}", method),
+    };
+    let resp = serde_json::json!({ "jsonrpc": "2.0", "id": req["id"], "result": result });
+
+    Ok(Response::new(Body::from(resp.to_string())))
+}
+
+/// Returns the uri of a `stub_node`.
+fn spawn_stub_node(state: Arc<Mutex<StubState>>, included: usize) -> Uri {
+    let service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move {
+            Ok::<_, hyper::Error>(service_fn(move |req| {
+                stub_node(req, state.clone(), included)
+            }))
+        }
+    });
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(service);
+    let uri = Uri::try_from(format!("http://{}", server.local_addr())).unwrap();
+    tokio::spawn(server);
+
+    uri
+}
+
+fn tx_manager(max_bumps: u32) -> TxManager {
+    let wallet = "0x2bbf6c1f5a7e3e4c23df4aaa59ab6b5a7fc3b4a3a1fa1a0e0b73c0b2a6b0f5c1"
+        .parse::<LocalWallet>()
+        .unwrap()
+        .with_chain_id(99u64);
+
+    TxManager::new(wallet.into(), Duration::from_millis(50), max_bumps, 2)
+}
+
+#[tokio::test]
+async fn tx_manager_replacement() {
+    let state: Arc<Mutex<StubState>> = Arc::default();
+    let uri = spawn_stub_node(state.clone(), 1);
+    let receipt = tx_manager(3)
+        .send(&uri, Some(Address::zero()), U256::one(), vec![])
+        .await
+        .expect("receipt");
+
+    // the first version was replaced and the replacement got included
+    let state = state.lock().unwrap();
+    let sent = &state.sent;
+    assert_eq!(sent.len(), 2);
+    assert_ne!(sent[0], sent[1]);
+    assert_eq!(receipt.transaction_hash, H256::from(keccak256(&sent[1])));
+}
+
+#[tokio::test]
+async fn tx_manager_resume_after_timeout() {
+    let state: Arc<Mutex<StubState>> = Arc::default();
+    let uri = spawn_stub_node(state.clone(), 2);
+    let tx_manager = tx_manager(1);
+
+    let res = tx_manager
+        .send(&uri, Some(Address::zero()), U256::one(), vec![])
+        .await;
+    assert!(matches!(res, Err(TxError::Timeout(_))), "{:?}", res);
+
+    // a retry replaces the pending transaction instead of sending a new one
+    let receipt = tx_manager
+        .send(&uri, Some(Address::zero()), U256::one(), vec![])
+        .await
+        .expect("receipt");
+    let state = state.lock().unwrap();
+    assert_eq!(state.estimates, 1);
+    assert_eq!(state.sent.len(), 3);
+    assert_eq!(
+        receipt.transaction_hash,
+        H256::from(keccak256(&state.sent[2]))
+    );
+}
+
+#[test]
+fn tx_manager_bump_fees() {
+    let gas_price = U256::from(100);
+    let mut tx = Eip1559TransactionRequest::new()
+        .max_priority_fee_per_gas(1u64)
+        .max_fee_per_gas(1000u64);
+
+    bump_fees(&mut tx, gas_price);
+    assert_eq!(tx.max_priority_fee_per_gas, Some(U256::from(2)));
+    assert_eq!(
+        tx.max_fee_per_gas,
+        Some(U256::from(1000 * (100 + FEE_BUMP_PERCENT) / 100))
+    );
+
+    // follows the current gas price
+    let gas_price = U256::from(10_000);
+    bump_fees(&mut tx, gas_price);
+    assert_eq!(tx.max_fee_per_gas, Some(gas_price * 2u64));
+}
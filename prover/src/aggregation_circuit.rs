@@ -49,6 +49,7 @@ type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip
 pub type PoseidonTranscript<L, S> =
     system::halo2::transcript::halo2::PoseidonTranscript<G1Affine, L, S, T, RATE, R_F, R_P>;
 
+#[derive(Clone)]
 pub struct Snark {
     pub protocol: Protocol<G1Affine>,
     pub instances: Vec<Vec<Fr>>,
@@ -104,12 +105,16 @@ impl SnarkWitness {
     }
 }
 
+/// Returns the accumulator and the assigned instances of all `snarks` in order.
 pub fn aggregate<'a>(
     svk: &Svk,
     loader: &Rc<Halo2Loader<'a>>,
     snarks: &[SnarkWitness],
     as_proof: Value<&'_ [u8]>,
-) -> KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>> {
+) -> (
+    KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    Vec<loader::halo2::Scalar<'a, G1Affine, BaseFieldEccChip>>,
+) {
     let assign_instances = |instances: &[Vec<Value<Fr>>]| {
         instances
             .iter()
@@ -122,6 +127,7 @@ pub fn aggregate<'a>(
             .collect_vec()
     };
 
+    let mut snark_instances = Vec::new();
     let accumulators = snarks
         .iter()
         .flat_map(|snark| {
@@ -130,7 +136,9 @@ pub fn aggregate<'a>(
             let mut transcript =
                 PoseidonTranscript::<Rc<Halo2Loader>, _>::new(loader, snark.proof());
             let proof = Plonk::read_proof(svk, &protocol, &instances, &mut transcript).unwrap();
-            Plonk::succinct_verify(svk, &protocol, &instances, &proof).unwrap()
+            let accumulators = Plonk::succinct_verify(svk, &protocol, &instances, &proof).unwrap();
+            snark_instances.extend(instances.into_iter().flatten());
+            accumulators
         })
         .collect_vec();
 
@@ -140,7 +148,7 @@ pub fn aggregate<'a>(
         As::verify(&Default::default(), &accumulators, &proof).unwrap()
     };
 
-    acccumulator
+    (acccumulator, snark_instances)
 }
 
 #[derive(Clone)]
@@ -221,10 +229,15 @@ impl AggregationCircuit {
             (accumulator, transcript.finalize())
         };
 
+        // the accumulator limbs, followed by the instances of all snarks
+        // so that a verifier can tell which blocks the proof covers
         let KzgAccumulator { lhs, rhs } = accumulator;
         let instances = [lhs.x, lhs.y, rhs.x, rhs.y]
             .map(fe_to_limbs::<_, _, LIMBS, BITS>)
-            .concat();
+            .concat()
+            .into_iter()
+            .chain(snarks.iter().flat_map(|snark| snark.instances.concat()))
+            .collect_vec();
 
         Self {
             svk,
@@ -238,8 +251,8 @@ impl AggregationCircuit {
         (0..4 * LIMBS).map(|idx| (0, idx)).collect()
     }
 
-    pub fn num_instance() -> Vec<usize> {
-        vec![4 * LIMBS]
+    pub fn num_instance(&self) -> Vec<usize> {
+        vec![self.instances.len()]
     }
 
     pub fn instance(&self) -> Vec<Vec<Fr>> {
@@ -286,14 +299,15 @@ impl Circuit<Fr> for AggregationCircuit {
 
         range_chip.load_table(&mut layouter)?;
 
-        let accumulator_limbs = layouter.assign_region(
+        let (accumulator_limbs, snark_instances) = layouter.assign_region(
             || "",
             |region| {
                 let ctx = RegionCtx::new(region, 0);
 
                 let ecc_chip = config.ecc_chip();
                 let loader = Halo2Loader::new(ecc_chip, ctx);
-                let accumulator = aggregate(&self.svk, &loader, &self.snarks, self.as_proof());
+                let (accumulator, snark_instances) =
+                    aggregate(&self.svk, &loader, &self.snarks, self.as_proof());
 
                 let accumulator_limbs = [accumulator.lhs, accumulator.rhs]
                     .iter()
@@ -305,13 +319,17 @@ impl Circuit<Fr> for AggregationCircuit {
                     .collect::<Result<Vec<_>, plonk::Error>>()?
                     .into_iter()
                     .flatten();
+                let snark_instances = snark_instances
+                    .iter()
+                    .map(|instance| instance.assigned())
+                    .collect_vec();
 
-                Ok(accumulator_limbs)
+                Ok((accumulator_limbs, snark_instances))
             },
         )?;
 
-        for (row, limb) in accumulator_limbs.enumerate() {
-            main_gate.expose_public(layouter.namespace(|| ""), limb, row)?;
+        for (row, cell) in accumulator_limbs.chain(snark_instances).enumerate() {
+            main_gate.expose_public(layouter.namespace(|| ""), cell, row)?;
         }
 
         Ok(())
@@ -1,9 +1,16 @@
 use env_logger::Env;
 use prover::shared_state::SharedState;
+use prover::worker::run_worker;
 use std::env::var;
 use zkevm_common::prover::*;
 
 /// This command generates and prints the proofs to stdout.
+///
+/// If invoked with `worker` as the first argument, then the command reads
+/// `ProofRequestOptions` lines as json from stdin and writes a result line
+/// to stdout for each instead. This mode is used by proverd to compute proofs
+/// in a subprocess.
+///
 /// Required environment variables:
 /// - PROVERD_BLOCK_NUM - the block number to generate the proof for
 /// - PROVERD_RPC_URL - a geth http rpc that supports the debug namespace
@@ -12,6 +19,11 @@ use zkevm_common::prover::*;
 async fn main() {
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
 
+    if std::env::args().nth(1).as_deref() == Some("worker") {
+        run_worker().await;
+        return;
+    }
+
     let block_num: u64 = var("PROVERD_BLOCK_NUM")
         .expect("PROVERD_BLOCK_NUM env var")
         .parse()
@@ -1,8 +1,31 @@
 use clap::Parser;
+use clap::ValueEnum;
 use env_logger::Env;
 
+use prover::discovery::DnsDiscovery;
+use prover::discovery::FileDiscovery;
+use prover::discovery::PeerDiscovery;
+use prover::discovery::SrvDiscovery;
+use prover::discovery::StaticDiscovery;
 use prover::server::serve;
 use prover::shared_state::SharedState;
+use prover::task_store::FileTaskStore;
+use prover::worker::WorkerConfig;
+use std::sync::Arc;
+use std::time::Duration;
+use zkevm_common::auth::AuthConfig;
+
+#[derive(Clone, Debug, ValueEnum)]
+enum DiscoveryKind {
+    /// `PROVERD_LOOKUP` is a `HOSTNAME:PORT` that resolves to all nodes.
+    Dns,
+    /// `PROVERD_LOOKUP` is a comma separated list of `HOSTNAME:PORT` entries.
+    Static,
+    /// `PROVERD_LOOKUP` is a file with one `HOSTNAME:PORT` entry per line.
+    File,
+    /// `PROVERD_LOOKUP` is a fully qualified DNS name with SRV records.
+    Srv,
+}
 
 #[derive(Parser, Debug)]
 #[clap(version, about)]
@@ -14,7 +37,37 @@ pub(crate) struct ProverdConfig {
     bind: String,
     #[clap(long, env = "PROVERD_LOOKUP")]
     /// A `HOSTNAME:PORT` conformant string that will be used for DNS service discovery of other nodes.
+    /// The format depends on `discovery`.
     lookup: String,
+    #[clap(long, env = "PROVERD_DISCOVERY", value_enum, default_value_t = DiscoveryKind::Dns)]
+    /// The method to discover other nodes with.
+    discovery: DiscoveryKind,
+    #[clap(long, env = "PROVERD_DATA_DIR")]
+    /// A directory to persist the task queue in.
+    /// Tasks are only kept in memory if not set.
+    data_dir: Option<String>,
+    #[clap(long, env = "PROVERD_WORKER_CMD")]
+    /// Path to the `prover_cmd` binary. If set, then proofs are computed
+    /// in subprocesses of `prover_cmd worker`, one per slot at a time.
+    worker_cmd: Option<String>,
+    #[clap(long, env = "PROVERD_WORKER_MEMORY_LIMIT")]
+    /// Memory limit in MiB for each worker subprocess.
+    worker_memory_limit: Option<u64>,
+    #[clap(long, env = "PROVERD_SLOTS", default_value_t = 1)]
+    /// The number of tasks to compute in parallel.
+    slots: usize,
+    #[clap(long, env = "PROVERD_LEASE_DURATION", default_value_t = 30)]
+    /// The validity of a task claim in seconds.
+    /// A task is recovered by other nodes if its owner didn't renew the claim in time.
+    lease_duration: u64,
+    #[clap(long, env = "PROVERD_AUTH_TOKEN")]
+    /// A bearer token that grants access to all json-rpc methods,
+    /// also used for requests to other nodes.
+    /// The json-rpc interface is public if neither token is set.
+    auth_token: Option<String>,
+    #[clap(long, env = "PROVERD_AUTH_READ_TOKEN")]
+    /// A bearer token that grants access to read-only json-rpc methods.
+    auth_read_token: Option<String>,
 }
 
 #[tokio::main]
@@ -22,7 +75,40 @@ async fn main() {
     let config = ProverdConfig::parse();
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
 
-    let shared_state = SharedState::new(SharedState::random_worker_id(), Some(config.lookup));
+    let discovery: Arc<dyn PeerDiscovery> = match config.discovery {
+        DiscoveryKind::Dns => Arc::new(DnsDiscovery {
+            lookup: config.lookup.clone(),
+        }),
+        DiscoveryKind::Static => Arc::new(StaticDiscovery::from_list(&config.lookup)),
+        DiscoveryKind::File => Arc::new(FileDiscovery {
+            path: config.lookup.clone(),
+        }),
+        DiscoveryKind::Srv => Arc::new(SrvDiscovery {
+            name: config.lookup.clone(),
+        }),
+    };
+    let mut shared_state = SharedState::new(SharedState::random_worker_id(), Some(config.lookup))
+        .with_discovery(discovery)
+        .with_slots(config.slots)
+        .await;
+    shared_state.ro.lease_duration = Duration::from_secs(config.lease_duration);
+    shared_state.ro.auth = AuthConfig {
+        token: config.auth_token,
+        read_token: config.auth_read_token,
+    };
+    if let Some(cmd) = config.worker_cmd {
+        shared_state.ro.worker = Some(WorkerConfig::new(
+            cmd,
+            config.worker_memory_limit.map(|mib| mib << 20),
+        ));
+    }
+    if let Some(data_dir) = config.data_dir {
+        let task_store = FileTaskStore::new(&data_dir).expect("task store");
+        shared_state = shared_state
+            .with_task_store(Arc::new(task_store))
+            .await
+            .expect("restore tasks");
+    }
     {
         // start the http server
         let h1 = serve(&shared_state, &config.bind);
@@ -38,12 +124,12 @@ async fn main() {
             loop {
                 let ctx = ctx.clone();
                 // enclose this call to catch panics which may
-                // occur due to network services
-                let _ = tokio::spawn(async move {
+                // occur due to network services.
+                // not awaited, slots that become free are filled by the next invocation
+                tokio::spawn(async move {
                     log::debug!("task: duty_cycle");
                     ctx.duty_cycle().await;
-                })
-                .await;
+                });
                 tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
             }
         });
@@ -0,0 +1,342 @@
+use std::io::Read;
+use std::io::Write;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+/// Resolves the addresses of all proverd nodes, including this instance.
+/// Invoked before each round of peer communication, on a thread where blocking is fine.
+pub trait PeerDiscovery: Send + Sync {
+    fn peers(&self) -> Result<Vec<SocketAddr>, String>;
+}
+
+/// Resolves a single `HOSTNAME:PORT` that maps to multiple addresses,
+/// e.g. the service name inside docker-compose.
+pub struct DnsDiscovery {
+    pub lookup: String,
+}
+
+impl PeerDiscovery for DnsDiscovery {
+    fn peers(&self) -> Result<Vec<SocketAddr>, String> {
+        self.lookup
+            .to_socket_addrs()
+            .map(|addrs| addrs.collect())
+            .map_err(|e| format!("{}: {}", self.lookup, e))
+    }
+}
+
+/// A fixed list of `HOSTNAME:PORT` entries.
+/// Entries that fail to resolve are skipped.
+pub struct StaticDiscovery {
+    pub peers: Vec<String>,
+}
+
+impl StaticDiscovery {
+    /// Expects a comma separated list of `HOSTNAME:PORT` entries.
+    pub fn from_list(list: &str) -> Self {
+        Self {
+            peers: parse_peer_list(list),
+        }
+    }
+}
+
+impl PeerDiscovery for StaticDiscovery {
+    fn peers(&self) -> Result<Vec<SocketAddr>, String> {
+        Ok(resolve_all(&self.peers))
+    }
+}
+
+/// Reads `HOSTNAME:PORT` entries from a file on each invocation,
+/// separated by newlines or commas. Lines starting with `#` are ignored.
+pub struct FileDiscovery {
+    pub path: String,
+}
+
+impl PeerDiscovery for FileDiscovery {
+    fn peers(&self) -> Result<Vec<SocketAddr>, String> {
+        let content =
+            std::fs::read_to_string(&self.path).map_err(|e| format!("{}: {}", self.path, e))?;
+        let list: Vec<&str> = content
+            .lines()
+            .map(|e| e.trim())
+            .filter(|e| !e.starts_with('#'))
+            .collect();
+
+        Ok(resolve_all(&parse_peer_list(&list.join(","))))
+    }
+}
+
+/// Queries the SRV records of `name`, e.g. `_rpc._tcp.proverd.default.svc.cluster.local`,
+/// from the nameservers in `/etc/resolv.conf`, in order until one of them responds.
+/// `name` must be fully qualified, search domains are not applied.
+/// Every target is a peer, ordered by priority and weight, see `SrvRecord`.
+pub struct SrvDiscovery {
+    pub name: String,
+}
+
+impl PeerDiscovery for SrvDiscovery {
+    fn peers(&self) -> Result<Vec<SocketAddr>, String> {
+        let mut records = query_srv(&self.name)?;
+        records.sort();
+        let targets: Vec<String> = records
+            .iter()
+            .map(|e| format!("{}:{}", e.target, e.port))
+            .collect();
+
+        Ok(resolve_all(&targets))
+    }
+}
+
+/// A SRV record, ordered by ascending priority and descending weight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrvRecord {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: String,
+}
+
+impl Ord for SrvRecord {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then(other.weight.cmp(&self.weight))
+            .then_with(|| self.target.cmp(&other.target))
+            .then(self.port.cmp(&other.port))
+    }
+}
+
+impl PartialOrd for SrvRecord {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn parse_peer_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|e| e.trim())
+        .filter(|e| !e.is_empty())
+        .map(|e| e.to_string())
+        .collect()
+}
+
+fn resolve_all(peers: &[String]) -> Vec<SocketAddr> {
+    let mut ret = Vec::new();
+    for peer in peers {
+        match peer.to_socket_addrs() {
+            Err(err) => log::warn!("discovery: skipping {}: {}", peer, err),
+            Ok(addrs) => ret.extend(addrs),
+        }
+    }
+
+    ret
+}
+
+const DNS_TYPE_SRV: u16 = 33;
+const DNS_CLASS_IN: u16 = 1;
+const DNS_FLAG_TRUNCATED: u16 = 0x0200;
+const DNS_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn nameservers() -> Result<Vec<SocketAddr>, String> {
+    let resolv_conf = std::fs::read_to_string("/etc/resolv.conf").map_err(|e| e.to_string())?;
+    let ret: Vec<SocketAddr> = resolv_conf
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .filter_map(|e| e.trim().parse::<IpAddr>().ok())
+        .map(|ip| SocketAddr::new(ip, 53))
+        .collect();
+    if ret.is_empty() {
+        return Err("no nameserver in /etc/resolv.conf".to_string());
+    }
+
+    Ok(ret)
+}
+
+/// Returns all SRV records for `name` from the first nameserver that responds.
+fn query_srv(name: &str) -> Result<Vec<SrvRecord>, String> {
+    let mut last_err = String::new();
+    for server in nameservers()? {
+        match query_srv_from(server, name) {
+            Ok(records) => return Ok(records),
+            Err(err) => {
+                log::warn!("discovery: nameserver {}: {}", server, err);
+                last_err = err;
+            }
+        }
+    }
+
+    Err(last_err)
+}
+
+/// Returns all SRV records for `name` from `server`.
+/// Repeats the query over TCP if the UDP response is truncated.
+fn query_srv_from(server: SocketAddr, name: &str) -> Result<Vec<SrvRecord>, String> {
+    let bind_addr = match server {
+        SocketAddr::V4(_) => "0.0.0.0:0",
+        SocketAddr::V6(_) => "[::]:0",
+    };
+    let socket = UdpSocket::bind(bind_addr).map_err(|e| e.to_string())?;
+    socket
+        .set_read_timeout(Some(DNS_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+
+    let id: u16 = rand::random();
+    let query = encode_query(id, name)?;
+    socket.send_to(&query, server).map_err(|e| e.to_string())?;
+
+    let mut buf = [0u8; 4096];
+    loop {
+        let (len, from) = socket.recv_from(&mut buf).map_err(|e| e.to_string())?;
+        if from != server || len < 2 || u16::from_be_bytes([buf[0], buf[1]]) != id {
+            // not our response
+            continue;
+        }
+        if read_u16(&buf[..len], 2)? & DNS_FLAG_TRUNCATED != 0 {
+            log::debug!("discovery: truncated dns response, retrying over tcp");
+            return query_tcp(server, &query).and_then(|resp| parse_srv_response(&resp));
+        }
+
+        return parse_srv_response(&buf[..len]);
+    }
+}
+
+/// Sends `query` to `server` over TCP and returns the response.
+fn query_tcp(server: SocketAddr, query: &[u8]) -> Result<Vec<u8>, String> {
+    let mut stream = TcpStream::connect_timeout(&server, DNS_TIMEOUT).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(DNS_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    stream
+        .set_write_timeout(Some(DNS_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+
+    // messages are prefixed with their length
+    let mut msg = (query.len() as u16).to_be_bytes().to_vec();
+    msg.extend_from_slice(query);
+    stream.write_all(&msg).map_err(|e| e.to_string())?;
+
+    let mut len = [0u8; 2];
+    stream.read_exact(&mut len).map_err(|e| e.to_string())?;
+    let mut resp = vec![0u8; u16::from_be_bytes(len) as usize];
+    stream.read_exact(&mut resp).map_err(|e| e.to_string())?;
+    if read_u16(&resp, 0)? != read_u16(query, 0)? {
+        return Err("dns: unexpected response id".to_string());
+    }
+
+    Ok(resp)
+}
+
+fn encode_query(id: u16, name: &str) -> Result<Vec<u8>, String> {
+    let mut ret = Vec::with_capacity(name.len() + 18);
+    ret.extend_from_slice(&id.to_be_bytes());
+    // flags: recursion desired
+    ret.extend_from_slice(&0x0100u16.to_be_bytes());
+    // qdcount, ancount, nscount, arcount
+    ret.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("invalid dns name: {}", name));
+        }
+        ret.push(label.len() as u8);
+        ret.extend_from_slice(label.as_bytes());
+    }
+    ret.push(0);
+    ret.extend_from_slice(&DNS_TYPE_SRV.to_be_bytes());
+    ret.extend_from_slice(&DNS_CLASS_IN.to_be_bytes());
+
+    Ok(ret)
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> Result<u16, String> {
+    buf.get(pos..pos + 2)
+        .map(|e| u16::from_be_bytes([e[0], e[1]]))
+        .ok_or_else(|| "dns: unexpected end of message".to_string())
+}
+
+/// Reads a (compressed) name at `pos`.
+/// Returns the name and the position after the name.
+fn read_name(buf: &[u8], mut pos: usize) -> Result<(String, usize), String> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut end = None;
+
+    loop {
+        let len = *buf.get(pos).ok_or("dns: unexpected end of message")? as usize;
+        if len == 0 {
+            break;
+        }
+        if len & 0xc0 == 0xc0 {
+            let target = (read_u16(buf, pos)? & 0x3fff) as usize;
+            // only pointers to prior occurrences are valid, this also rules out loops
+            if target >= pos {
+                return Err("dns: invalid compression pointer".to_string());
+            }
+            end.get_or_insert(pos + 2);
+            pos = target;
+            continue;
+        }
+        if len & 0xc0 != 0 {
+            return Err("dns: invalid label type".to_string());
+        }
+
+        let label = buf
+            .get(pos + 1..pos + 1 + len)
+            .ok_or("dns: unexpected end of message")?;
+        labels.push(String::from_utf8_lossy(label).to_string());
+        pos += 1 + len;
+    }
+
+    Ok((labels.join("."), end.unwrap_or(pos + 1)))
+}
+
+/// Parses the SRV records of a dns response message.
+/// Other records are skipped.
+pub fn parse_srv_response(buf: &[u8]) -> Result<Vec<SrvRecord>, String> {
+    let flags = read_u16(buf, 2)?;
+    if flags & 0x000f != 0 {
+        return Err(format!("dns: response code {}", flags & 0x000f));
+    }
+    if flags & DNS_FLAG_TRUNCATED != 0 {
+        return Err("dns: truncated response".to_string());
+    }
+
+    let qdcount = read_u16(buf, 4)?;
+    let ancount = read_u16(buf, 6)?;
+    let mut pos = 12;
+    for _ in 0..qdcount {
+        let (_, next) = read_name(buf, pos)?;
+        // qtype, qclass
+        pos = next + 4;
+    }
+
+    let mut ret = Vec::new();
+    for _ in 0..ancount {
+        let (_, next) = read_name(buf, pos)?;
+        let rtype = read_u16(buf, next)?;
+        // type, class, ttl
+        let rdlength = read_u16(buf, next + 8)? as usize;
+        let rdata = next + 10;
+        if rdata + rdlength > buf.len() {
+            return Err("dns: unexpected end of message".to_string());
+        }
+        if rtype == DNS_TYPE_SRV {
+            // priority, weight, port, target
+            let (target, end) = read_name(buf, rdata + 6)?;
+            if end > rdata + rdlength {
+                return Err("dns: invalid SRV record".to_string());
+            }
+            ret.push(SrvRecord {
+                priority: read_u16(buf, rdata)?,
+                weight: read_u16(buf, rdata + 2)?,
+                port: read_u16(buf, rdata + 4)?,
+                target,
+            });
+        }
+        pos = rdata + rdlength;
+    }
+
+    Ok(ret)
+}
@@ -12,6 +12,10 @@ pub mod aggregation_circuit;
 pub mod circuit_autogen;
 pub mod circuit_witness;
 pub mod circuits;
+pub mod discovery;
+pub mod metrics;
 pub mod server;
 pub mod shared_state;
+pub mod task_store;
 pub mod utils;
+pub mod worker;
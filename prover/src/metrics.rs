@@ -0,0 +1,153 @@
+use crate::shared_state::RwState;
+use prometheus::Counter;
+use prometheus::CounterVec;
+use prometheus::GaugeVec;
+use prometheus::Histogram;
+use prometheus::HistogramOpts;
+use prometheus::HistogramVec;
+use prometheus::Opts;
+use prometheus::Registry;
+use zkevm_common::metrics::*;
+
+/// Metrics of proverd, exposed via `/metrics`.
+///
+/// Note: `keygen_duration` and `pk_cache` are only recorded if the proofs
+/// are computed in this process and not in a `worker` subprocess.
+pub struct ProverMetrics {
+    pub tasks: GaugeVec,
+    pub slots: GaugeVec,
+    pub proof_duration: HistogramVec,
+    pub keygen_duration: Histogram,
+    pub pk_cache: CounterVec,
+    pub failures: CounterVec,
+    pub merge_duration: Histogram,
+    pub merge_failures: Counter,
+    registry: Registry,
+}
+
+impl Default for ProverMetrics {
+    fn default() -> Self {
+        let registry = Registry::new();
+        let duration = |name: &str, help: &str| {
+            HistogramOpts::new(name, help).buckets(DURATION_BUCKETS.to_vec())
+        };
+
+        Self {
+            tasks: register(
+                &registry,
+                GaugeVec::new(
+                    Opts::new("proverd_tasks", "Number of tasks in the queue by state"),
+                    &["state"],
+                )
+                .unwrap(),
+            ),
+            slots: register(
+                &registry,
+                GaugeVec::new(
+                    Opts::new("proverd_slots", "Number of proving slots by state"),
+                    &["state"],
+                )
+                .unwrap(),
+            ),
+            proof_duration: register(
+                &registry,
+                HistogramVec::new(
+                    duration(
+                        "proverd_proof_duration_seconds",
+                        "Proof computation time by circuit label",
+                    ),
+                    &["label"],
+                )
+                .unwrap(),
+            ),
+            keygen_duration: register(
+                &registry,
+                Histogram::with_opts(duration(
+                    "proverd_keygen_duration_seconds",
+                    "Proving key generation time",
+                ))
+                .unwrap(),
+            ),
+            pk_cache: register(
+                &registry,
+                CounterVec::new(
+                    Opts::new(
+                        "proverd_pk_cache_requests_total",
+                        "Proving key cache lookups by result",
+                    ),
+                    &["result"],
+                )
+                .unwrap(),
+            ),
+            failures: register(
+                &registry,
+                CounterVec::new(
+                    Opts::new("proverd_task_failures_total", "Failed tasks by error kind"),
+                    &["kind"],
+                )
+                .unwrap(),
+            ),
+            merge_duration: register(
+                &registry,
+                Histogram::with_opts(duration(
+                    "proverd_peer_merge_duration_seconds",
+                    "Latency of merging the tasks of a peer",
+                ))
+                .unwrap(),
+            ),
+            merge_failures: register(
+                &registry,
+                Counter::new(
+                    "proverd_peer_merge_failures_total",
+                    "Failed requests to peers while merging tasks",
+                )
+                .unwrap(),
+            ),
+            registry,
+        }
+    }
+}
+
+impl ProverMetrics {
+    /// Samples the queue and slot gauges from `rw` and renders all metrics.
+    pub fn render(&self, rw: &RwState) -> String {
+        let mut pending = 0;
+        let mut failed = 0;
+        let mut completed = 0;
+        for task in rw.tasks.iter() {
+            match &task.result {
+                None => pending += 1,
+                Some(Err(_)) => failed += 1,
+                Some(Ok(_)) => completed += 1,
+            }
+        }
+        let busy = rw.slots.iter().filter(|e| e.obtained).count();
+
+        // running tasks are still pending in the queue
+        self.tasks
+            .with_label_values(&["queued"])
+            .set(pending.saturating_sub(busy) as f64);
+        self.tasks.with_label_values(&["running"]).set(busy as f64);
+        self.tasks.with_label_values(&["failed"]).set(failed as f64);
+        self.tasks
+            .with_label_values(&["completed"])
+            .set(completed as f64);
+        self.slots.with_label_values(&["busy"]).set(busy as f64);
+        self.slots
+            .with_label_values(&["free"])
+            .set((rw.slots.len() - busy) as f64);
+
+        render(&self.registry)
+    }
+}
+
+/// Classifies the error of a failed task for `ProverMetrics::failures`.
+pub fn error_kind(err: &str) -> &'static str {
+    if err.starts_with("worker:") {
+        "worker"
+    } else if err.starts_with("No circuit parameters found") {
+        "circuit_params"
+    } else {
+        "other"
+    }
+}
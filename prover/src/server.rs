@@ -5,6 +5,7 @@ use hyper::body::HttpBody;
 use hyper::header::HeaderValue;
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use zkevm_common::auth::Scope;
 use zkevm_common::json_rpc::JsonRpcError;
 use zkevm_common::json_rpc::JsonRpcRequest;
 use zkevm_common::json_rpc::JsonRpcResponse;
@@ -44,7 +45,9 @@ fn set_headers(headers: &mut hyper::HeaderMap, extended: bool) {
         );
         headers.insert(
             "access-control-allow-headers",
-            HeaderValue::from_static("origin, content-type, accept, x-requested-with"),
+            HeaderValue::from_static(
+                "origin, content-type, accept, x-requested-with, authorization",
+            ),
         );
         headers.insert("access-control-max-age", HeaderValue::from_static("300"));
     }
@@ -82,7 +85,8 @@ async fn handle_request(
         // done.
         (&Method::GET, "/status") => {
             let rw = shared_state.rw.lock().await;
-            let is_busy = rw.pending.is_some() || rw.tasks.iter().any(|e| e.result.is_none());
+            let is_busy = rw.slots.iter().any(|e| e.pending.is_some())
+                || rw.tasks.iter().any(|e| e.result.is_none());
             drop(rw);
 
             let mut resp = Response::default();
@@ -94,8 +98,24 @@ async fn handle_request(
             Ok(resp)
         }
 
+        // prometheus metrics
+        (&Method::GET, "/metrics") => {
+            let rw = shared_state.rw.lock().await;
+            let payload = shared_state.ro.metrics.render(&rw);
+            drop(rw);
+
+            let mut resp = Response::new(Body::from(payload));
+            resp.headers_mut().insert(
+                "content-type",
+                HeaderValue::from_static("text/plain; version=0.0.4"),
+            );
+            Ok(resp)
+        }
+
         // json-rpc
         (&Method::POST, "/") => {
+            let auth = shared_state.ro.auth.clone();
+            let headers = req.headers().clone();
             let body_bytes = hyper::body::aggregate(req.into_body())
                 .await
                 .unwrap()
@@ -120,6 +140,23 @@ async fn handle_request(
             }
 
             let json_req = json_req.unwrap();
+            if let Err(err) = auth.check(&headers, method_scope(json_req.method.as_str())) {
+                let payload = serde_json::to_vec(&JsonRpcResponseError {
+                    jsonrpc: "2.0".to_string(),
+                    id: json_req.id,
+                    error: JsonRpcError {
+                        // server error
+                        code: -32001,
+                        message: err,
+                    },
+                })
+                .unwrap();
+                let mut resp = Response::new(Body::from(payload));
+                *resp.status_mut() = StatusCode::UNAUTHORIZED;
+                set_headers(resp.headers_mut(), false);
+                return Ok(resp);
+            }
+
             let result: Result<serde_json::Value, String> =
                 handle_method(json_req.method.as_str(), &json_req.params, &shared_state).await;
             let payload = match result {
@@ -161,6 +198,14 @@ async fn handle_request(
     }
 }
 
+/// The scope required for `method`, read-only methods are available with `Scope::Read`.
+fn method_scope(method: &str) -> Scope {
+    match method {
+        "info" | "status" | "circuit_config" => Scope::Read,
+        _ => Scope::Full,
+    }
+}
+
 async fn handle_method(
     method: &str,
     params: &[serde_json::Value],
@@ -200,7 +245,44 @@ async fn handle_method(
             Ok(serde_json::to_value(circuit_config).unwrap())
         }
 
-        // TODO: Add the abilitity to abort the current task.
+        // cancels a pending or running task
+        "cancel" => {
+            let options = params.get(0).ok_or("expected struct ProofRequestOptions")?;
+            let options: ProofRequestOptions =
+                serde_json::from_value(options.to_owned()).map_err(|e| e.to_string())?;
+
+            Ok(serde_json::Value::Bool(shared_state.cancel(&options).await))
+        }
+
+        // claims a task for a peer, see `SharedState::accept_claim`
+        // used internally for p2p communication
+        "claim" => {
+            let options = params.get(0).ok_or("expected struct ProofRequestOptions")?;
+            let options: ProofRequestOptions =
+                serde_json::from_value(options.to_owned()).map_err(|e| e.to_string())?;
+            let lease = params.get(1).ok_or("expected struct TaskLease")?;
+            let lease: TaskLease =
+                serde_json::from_value(lease.to_owned()).map_err(|e| e.to_string())?;
+
+            Ok(serde_json::Value::Bool(
+                shared_state.accept_claim(&options, &lease).await,
+            ))
+        }
+
+        // withdraws a claim of a peer
+        // used internally for p2p communication
+        "release" => {
+            let options = params.get(0).ok_or("expected struct ProofRequestOptions")?;
+            let options: ProofRequestOptions =
+                serde_json::from_value(options.to_owned()).map_err(|e| e.to_string())?;
+            let lease = params.get(1).ok_or("expected struct TaskLease")?;
+            let lease: TaskLease =
+                serde_json::from_value(lease.to_owned()).map_err(|e| e.to_string())?;
+
+            Ok(serde_json::Value::Bool(
+                shared_state.release(&options, &lease).await,
+            ))
+        }
 
         // returns `NodeInformation`
         // used internally for p2p communication
@@ -209,17 +291,17 @@ async fn handle_method(
         // returns `NodeStatus`
         // used internally for p2p communication
         "status" => {
-            let mut pending_task = None;
             let rw = shared_state.rw.lock().await;
-
-            if let Some(val) = &rw.pending {
-                pending_task = Some(val.clone());
-            }
-
             let ret = NodeStatus {
                 id: shared_state.ro.node_id.clone(),
-                task: pending_task,
-                obtained: rw.obtained,
+                slots: rw
+                    .slots
+                    .iter()
+                    .map(|slot| SlotStatus {
+                        task: slot.pending.clone(),
+                        obtained: slot.obtained,
+                    })
+                    .collect(),
             };
             drop(rw);
 
@@ -244,10 +326,10 @@ async fn handle_method(
                 rw_state.pk_cache.clear();
             }
             if options.pending {
-                rw_state.tasks.retain(|e| e.result.is_some());
+                shared_state.retain_tasks(&mut rw_state.tasks, |e| e.result.is_some());
             }
             if options.completed {
-                rw_state.tasks.retain(|e| e.result.is_none());
+                shared_state.retain_tasks(&mut rw_state.tasks, |e| e.result.is_none());
             }
 
             Ok(serde_json::Value::Bool(true))
@@ -257,25 +339,18 @@ async fn handle_method(
         // the following methods can be used to programmatically
         // prune the `tasks` from the list.
         "flushAll" => {
-            shared_state.rw.lock().await.tasks.clear();
+            let mut rw_state = shared_state.rw.lock().await;
+            shared_state.retain_tasks(&mut rw_state.tasks, |_| false);
             Ok(serde_json::Value::Bool(true))
         }
         "flushPending" => {
-            shared_state
-                .rw
-                .lock()
-                .await
-                .tasks
-                .retain(|e| e.result.is_some());
+            let mut rw_state = shared_state.rw.lock().await;
+            shared_state.retain_tasks(&mut rw_state.tasks, |e| e.result.is_some());
             Ok(serde_json::Value::Bool(true))
         }
         "flushCompleted" => {
-            shared_state
-                .rw
-                .lock()
-                .await
-                .tasks
-                .retain(|e| e.result.is_none());
+            let mut rw_state = shared_state.rw.lock().await;
+            shared_state.retain_tasks(&mut rw_state.tasks, |e| e.result.is_none());
             Ok(serde_json::Value::Bool(true))
         }
         _ => Err("this method is not available".to_string()),
@@ -3,10 +3,19 @@ use crate::aggregation_circuit::PoseidonTranscript;
 use crate::aggregation_circuit::Snark;
 use crate::circuit_witness::CircuitWitness;
 use crate::circuits::*;
+use crate::discovery::DnsDiscovery;
+use crate::discovery::PeerDiscovery;
+use crate::metrics::error_kind;
+use crate::metrics::ProverMetrics;
+use crate::task_store::TaskChange;
+use crate::task_store::TaskStore;
 use crate::utils::collect_instance;
 use crate::utils::fixed_rng;
 use crate::utils::gen_num_instance;
 use crate::utils::gen_proof;
+use crate::utils::join_error_to_string;
+use crate::utils::unix_time_ms;
+use crate::worker::WorkerConfig;
 use crate::G1Affine;
 use crate::ProverKey;
 use crate::ProverParams;
@@ -15,23 +24,36 @@ use halo2_proofs::halo2curves::bn256::Fr;
 use halo2_proofs::plonk::Circuit;
 use halo2_proofs::plonk::{keygen_pk, keygen_vk};
 use halo2_proofs::poly::commitment::Params;
+use hyper::client::HttpConnector;
 use hyper::Uri;
 use plonk_verifier::loader::native::NativeLoader;
 use plonk_verifier::system::halo2::compile;
 use plonk_verifier::system::halo2::transcript::evm::EvmTranscript;
 use plonk_verifier::system::halo2::Config as PlonkConfig;
 use rand::{thread_rng, Rng};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::fmt::Write;
 use std::fs::File;
-use std::net::ToSocketAddrs;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
 use tokio::sync::Mutex;
+use tokio::sync::OnceCell;
 use zkevm_circuits::util::SubCircuit;
-use zkevm_common::json_rpc::jsonrpc_request_client;
+use zkevm_common::auth::AuthConfig;
+use zkevm_common::json_rpc::jsonrpc_call_with_token;
 use zkevm_common::prover::*;
 
+/// The number of cancelled tasks that are remembered, see `RwState::cancelled_tasks`.
+const CANCELLED_TASKS_HISTORY: usize = 1000;
+
 fn get_param_path(path: &String, k: usize) -> String {
     // try to automatically choose a file if the path ends with a `/`.
     match path.ends_with('/') {
@@ -61,6 +83,37 @@ fn get_or_gen_param(task_options: &ProofRequestOptions, k: usize) -> (Arc<Prover
     }
 }
 
+/// The key for `RwState::pk_cache`.
+pub fn pk_cache_key(circuit: &str, param_path: &str, circuit_config: &CircuitConfig) -> String {
+    format!("{}{}{:?}", circuit, param_path, circuit_config)
+}
+
+/// The scheduling policy for pending tasks, `Ordering::Less` is computed first.
+/// Only depends on replicated task data, so all nodes agree on the same order.
+///
+/// The order is:
+/// - higher `priority` first
+/// - real proofs before `mock` runs
+/// - fresh tasks before retries
+/// - lower block numbers first
+/// - the remaining identifying fields as a tie-breaker
+pub fn cmp_task_priority(a: &ProofRequest, b: &ProofRequest) -> Ordering {
+    let (a_opts, b_opts) = (&a.options, &b.options);
+
+    b_opts
+        .priority
+        .unwrap_or_default()
+        .cmp(&a_opts.priority.unwrap_or_default())
+        .then(a_opts.mock.cmp(&b_opts.mock))
+        .then((a.edition > 0).cmp(&(b.edition > 0)))
+        .then(a_opts.block.cmp(&b_opts.block))
+        .then(a_opts.to_block.cmp(&b_opts.to_block))
+        .then_with(|| a_opts.circuit.cmp(&b_opts.circuit))
+        .then_with(|| a_opts.rpc.cmp(&b_opts.rpc))
+        .then_with(|| a_opts.param.cmp(&b_opts.param))
+        .then(a_opts.aggregate.cmp(&b_opts.aggregate))
+}
+
 macro_rules! gen_proof {
     ($shared_state:expr, $task_options:expr, $witness:expr, $CIRCUIT:ident) => {{
         let witness = $witness;
@@ -76,11 +129,7 @@ macro_rules! gen_proof {
             "{}-{}",
             task_options.circuit, CIRCUIT_CONFIG.block_gas_limit
         );
-        let mut aggregation_proof = ProofResult::default();
-        aggregation_proof.label = format!(
-            "{}-{}-a",
-            task_options.circuit, CIRCUIT_CONFIG.block_gas_limit
-        );
+        let mut snark = None;
 
         if task_options.mock {
             // only run the mock prover
@@ -108,10 +157,7 @@ macro_rules! gen_proof {
             >(&witness, fixed_rng())?;
             // generate and cache the prover key
             let pk = {
-                let cache_key = format!(
-                    "{}{}{:?}",
-                    &task_options.circuit, &param_path, &CIRCUIT_CONFIG
-                );
+                let cache_key = pk_cache_key(&task_options.circuit, &param_path, &CIRCUIT_CONFIG);
                 shared_state
                     .gen_pk(&cache_key, &param, &circuit)
                     .await
@@ -142,50 +188,12 @@ macro_rules! gen_proof {
                     Instant::now().duration_since(time_started).as_millis() as u32;
                 circuit_proof.proof = proof.clone().into();
 
-                // aggregate the circuit proof
-                let time_started = Instant::now();
                 let protocol = compile(
                     param.as_ref(),
                     pk.get_vk(),
                     PlonkConfig::kzg().with_num_instance(gen_num_instance(&circuit_instance)),
                 );
-                let snark = Snark::new(protocol, circuit_instance, proof);
-
-                let (agg_params, agg_param_path) =
-                    get_or_gen_param(&task_options, CIRCUIT_CONFIG.min_k_aggregation);
-                aggregation_proof.k = agg_params.k() as u8;
-                let agg_circuit =
-                    AggregationCircuit::new(agg_params.as_ref(), [snark], fixed_rng());
-                let agg_pk = {
-                    let cache_key = format!(
-                        "{}{}{:?}ag",
-                        &task_options.circuit, &agg_param_path, &CIRCUIT_CONFIG
-                    );
-                    shared_state
-                        .gen_pk(&cache_key, &agg_params, &agg_circuit)
-                        .await
-                        .map_err(|e| e.to_string())?
-                };
-                let agg_instance = agg_circuit.instance();
-                aggregation_proof.instance = collect_instance(&agg_instance);
-                let proof = gen_proof::<
-                    _,
-                    _,
-                    EvmTranscript<G1Affine, _, _, _>,
-                    EvmTranscript<G1Affine, _, _, _>,
-                    _,
-                >(
-                    agg_params.as_ref(),
-                    &agg_pk,
-                    agg_circuit,
-                    agg_instance,
-                    fixed_rng(),
-                    task_options.mock_feedback,
-                    task_options.verify_proof,
-                );
-                aggregation_proof.duration =
-                    Instant::now().duration_since(time_started).as_millis() as u32;
-                aggregation_proof.proof = proof.into();
+                snark = Some(Snark::new(protocol, circuit_instance, proof));
             } else {
                 let time_started = Instant::now();
                 let proof = gen_proof::<
@@ -209,8 +217,8 @@ macro_rules! gen_proof {
             }
         }
 
-        // return
-        (CIRCUIT_CONFIG, circuit_proof, aggregation_proof)
+        // return, the snark is aggregated by the caller
+        (CIRCUIT_CONFIG, circuit_proof, snark)
     }};
 }
 
@@ -221,15 +229,72 @@ pub struct RoState {
     // a `HOSTNAME:PORT` conformant string that will be used for DNS service discovery of other
     // nodes
     pub node_lookup: Option<String>,
+    // resolves the other nodes, defaults to DNS discovery of `node_lookup`
+    pub discovery: Option<Arc<dyn PeerDiscovery>>,
+    // persists the task queue if set
+    pub task_store: Option<Arc<dyn TaskStore>>,
+    // the pending changes for `task_store`, see `SharedState::save_task`
+    pub task_store_tx: Option<mpsc::UnboundedSender<TaskChange>>,
+    // computes proofs in a subprocess if set
+    pub worker: Option<WorkerConfig>,
+    // the validity of a task claim, renewed while working on the task
+    pub lease_duration: Duration,
+    // tokens for the json-rpc interface, `auth.token` is also used for requests to peers
+    pub auth: AuthConfig,
+    pub metrics: Arc<ProverMetrics>,
+    // held while `duty_cycle` merges and obtains tasks
+    pub duty_cycle_lock: Arc<Mutex<()>>,
 }
 
-pub struct RwState {
-    pub tasks: Vec<ProofRequest>,
-    pub pk_cache: HashMap<String, Arc<ProverKey>>,
-    /// The current active task this instance wants to obtain or is working on.
+/// A unit of work of this instance, each slot computes one task at a time.
+#[derive(Default)]
+pub struct Slot {
+    /// The current active task this slot wants to obtain or is working on.
     pub pending: Option<ProofRequestOptions>,
-    /// `true` if this instance started working on `pending`
+    /// `true` if this slot started working on `pending`
     pub obtained: bool,
+    /// Signals the computation of `pending` to stop.
+    pub cancel: Option<oneshot::Sender<()>>,
+}
+
+impl Slot {
+    fn is_free(&self) -> bool {
+        self.pending.is_none() && !self.obtained
+    }
+
+    /// Clears `pending` and interrupts the computation - if running.
+    fn interrupt(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            let _ = cancel.send(());
+        }
+        self.pending = None;
+        self.obtained = false;
+    }
+}
+
+pub struct RwState {
+    pub tasks: Vec<ProofRequest>,
+    /// Proving keys by `pk_cache_key`, each key is generated at most once at a time,
+    /// see `SharedState::gen_pk`.
+    pub pk_cache: HashMap<String, Arc<OnceCell<Arc<ProverKey>>>>,
+    /// The slots of this instance, see `SharedState::with_slots`.
+    pub slots: Vec<Slot>,
+    /// Peers by the time in milliseconds since every request to them failed,
+    /// see `SharedState::obtain_task`.
+    pub unreachable_peers: HashMap<SocketAddr, u64>,
+    /// The latest cancelled tasks, these are not merged from peers again.
+    pub cancelled_tasks: VecDeque<ProofRequestOptions>,
+}
+
+impl RwState {
+    /// Interrupts every slot working on `options`.
+    fn interrupt_pending(&mut self, options: &ProofRequestOptions) {
+        for slot in self.slots.iter_mut() {
+            if slot.pending.as_ref() == Some(options) {
+                slot.interrupt();
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -243,17 +308,134 @@ impl SharedState {
         Self {
             ro: RoState {
                 node_id,
+                discovery: node_lookup
+                    .clone()
+                    .map(|lookup| Arc::new(DnsDiscovery { lookup }) as Arc<dyn PeerDiscovery>),
                 node_lookup,
+                task_store: None,
+                task_store_tx: None,
+                worker: None,
+                lease_duration: Duration::from_secs(30),
+                auth: AuthConfig::default(),
+                metrics: Arc::new(ProverMetrics::default()),
+                duty_cycle_lock: Arc::default(),
             },
             rw: Arc::new(Mutex::new(RwState {
                 tasks: Vec::new(),
                 pk_cache: HashMap::new(),
-                pending: None,
-                obtained: false,
+                slots: vec![Slot::default()],
+                unreachable_peers: HashMap::new(),
+                cancelled_tasks: VecDeque::new(),
             })),
         }
     }
 
+    /// Uses `discovery` to find other nodes instead of the DNS lookup of `node_lookup`.
+    pub fn with_discovery(mut self, discovery: Arc<dyn PeerDiscovery>) -> Self {
+        self.ro.discovery = Some(discovery);
+
+        self
+    }
+
+    /// Returns the addresses of all nodes - possibly including this instance.
+    async fn peer_addrs(&self) -> Result<Vec<SocketAddr>, String> {
+        match &self.ro.discovery {
+            None => Ok(Vec::new()),
+            Some(discovery) => {
+                // lookups block on the network or the filesystem
+                let discovery = discovery.clone();
+                tokio::task::spawn_blocking(move || discovery.peers())
+                    .await
+                    .map_err(join_error_to_string)?
+            }
+        }
+    }
+
+    /// Invokes the json-rpc `method` of the peer at `addr` with a timeout of 5 seconds.
+    /// Sends `AuthConfig::token` of this instance, if any.
+    async fn peer_request<T: Serialize + Send + Sync, R: DeserializeOwned>(
+        &self,
+        client: &hyper::Client<HttpConnector>,
+        addr: &SocketAddr,
+        method: &str,
+        params: T,
+    ) -> Result<R, String> {
+        let uri = Uri::try_from(format!("http://{}", addr)).map_err(|e| e.to_string())?;
+        let token = self.ro.auth.token.as_deref();
+
+        jsonrpc_call_with_token(5000, client, &uri, token, method, params)
+            .await
+            .map_err(String::from)
+    }
+
+    /// Allows to compute up to `count` tasks in parallel, defaults to 1.
+    pub async fn with_slots(self, count: usize) -> Self {
+        assert!(count > 0, "at least one slot is required");
+        self.rw.lock().await.slots = (0..count).map(|_| Slot::default()).collect();
+
+        self
+    }
+
+    /// Uses `task_store` to persist the task queue and restores all tasks
+    /// from a previous run.
+    pub async fn with_task_store(mut self, task_store: Arc<dyn TaskStore>) -> Result<Self, String> {
+        let tasks = task_store.load()?;
+        log::info!("restored {} tasks from task store", tasks.len());
+        self.rw.lock().await.tasks = tasks;
+
+        // applies the changes in order
+        let (tx, mut rx) = mpsc::unbounded_channel::<TaskChange>();
+        let task_store_copy = task_store.clone();
+        tokio::spawn(async move {
+            while let Some(change) = rx.recv().await {
+                let task_store = task_store_copy.clone();
+                match tokio::task::spawn_blocking(move || change.apply(task_store.as_ref())).await {
+                    Err(err) => log::error!(
+                        "task_store: failed to save task: {}",
+                        join_error_to_string(err)
+                    ),
+                    Ok(Err(err)) => log::error!("task_store: failed to save task: {}", err),
+                    Ok(Ok(())) => {}
+                }
+            }
+        });
+        self.ro.task_store = Some(task_store);
+        self.ro.task_store_tx = Some(tx);
+
+        Ok(self)
+    }
+
+    /// Writes `task` to the task store - if any.
+    /// The write happens in the background, thus the caller can hold the `rw` lock.
+    /// Errors are only logged, the in-memory queue stays authoritative.
+    pub fn save_task(&self, task: &ProofRequest) {
+        if let Some(tx) = &self.ro.task_store_tx {
+            let _ = tx.send(TaskChange::Save(task.clone()));
+        }
+    }
+
+    /// Removes the task for `options` from the task store - if any, see `save_task`.
+    pub fn remove_task(&self, options: &ProofRequestOptions) {
+        if let Some(tx) = &self.ro.task_store_tx {
+            let _ = tx.send(TaskChange::Remove(options.clone()));
+        }
+    }
+
+    /// Removes the tasks from `tasks` and the task store for which `keep` returns false.
+    pub fn retain_tasks(
+        &self,
+        tasks: &mut Vec<ProofRequest>,
+        keep: impl Fn(&ProofRequest) -> bool,
+    ) {
+        tasks.retain(|e| {
+            let kept = keep(e);
+            if !kept {
+                self.remove_task(&e.options);
+            }
+            kept
+        });
+    }
+
     /// Will return the result or error of the task if it's completed.
     /// Otherwise enqueues the task and returns `None`.
     /// `retry_if_error` enqueues the task again if it returned with an error
@@ -276,6 +458,8 @@ impl SharedState {
                     // will be a candidate in `duty_cycle` again
                     task.result = None;
                     task.edition += 1;
+                    task.lease = None;
+                    self.save_task(task);
                 } else {
                     log::debug!("completed: {:#?}", task);
                     return task.result.clone();
@@ -290,8 +474,11 @@ impl SharedState {
                 options: options.clone(),
                 result: None,
                 edition: 0,
+                lease: None,
             };
             log::debug!("enqueue: {:#?}", task);
+            rw.cancelled_tasks.retain(|e| e != options);
+            self.save_task(&task);
             rw.tasks.push(task);
         }
 
@@ -302,168 +489,219 @@ impl SharedState {
     /// - records if a task completed
     /// - starting a new task
     /// Blocks until completion but releases the lock of `self.rw` in between.
+    /// Returns early if another invocation is still merging and obtaining tasks.
     pub async fn duty_cycle(&self) {
-        // fix the 'world' view
-        if let Err(err) = self.merge_tasks_from_peers().await {
-            log::error!("merge_tasks_from_peers failed with: {}", err);
-            return;
+        // the slots this call obtained a task for
+        let mut handles = Vec::new();
+        {
+            // the invocation that holds the lock fills the free slots anyway
+            let _guard = match self.ro.duty_cycle_lock.try_lock() {
+                Ok(guard) => guard,
+                Err(_) => {
+                    log::debug!("duty_cycle: already in progress");
+                    return;
+                }
+            };
+
+            // fix the 'world' view
+            if let Err(err) = self.merge_tasks_from_peers().await {
+                log::error!("merge_tasks_from_peers failed with: {}", err);
+                return;
+            }
+
+            // tasks this node lost the race for
+            let mut skip: Vec<ProofRequestOptions> = Vec::new();
+
+            while let Some((slot_index, task, cancel_rx)) = self.obtain_slot(&mut skip).await {
+                let self_copy = self.clone();
+                handles.push(tokio::spawn(async move {
+                    self_copy.run_slot(slot_index, task, cancel_rx).await
+                }));
+            }
         }
 
-        let rw = self.rw.lock().await;
-        if rw.pending.is_some() || rw.obtained {
-            // already computing
-            return;
+        // blocks until all obtained tasks are done
+        for handle in handles {
+            let _ = handle.await;
         }
-        // find a pending task
-        let tasks: Vec<ProofRequestOptions> = rw
-            .tasks
-            .iter()
-            .filter(|&e| e.result.is_none())
-            .map(|e| e.options.clone())
-            .collect();
-        drop(rw);
+    }
 
-        for task in tasks {
-            // signals that this node wants to process this task
-            log::debug!("trying to obtain {:#?}", task);
-            self.rw.lock().await.pending = Some(task);
+    /// Reserves a free slot for the next pending task and claims the task
+    /// from all peers, see `obtain_task`.
+    /// Tasks that this instance failed to obtain are added to `skip`.
+    ///
+    /// Returns `None` if all slots are busy or there is nothing left to obtain.
+    /// Otherwise, returns the slot and task that this instance now owns
+    /// together with the receiving end of the cancellation signal for the slot.
+    pub async fn obtain_slot(
+        &self,
+        skip: &mut Vec<ProofRequestOptions>,
+    ) -> Option<(usize, ProofRequestOptions, oneshot::Receiver<()>)> {
+        loop {
+            // reserve a free slot and the next pending task under the same lock
+            // because other invocations of `duty_cycle` or claims of peers may run concurrently
+            let (slot_index, task, lease) = {
+                let mut rw = self.rw.lock().await;
+                // all slots busy?
+                let slot_index = rw.slots.iter().position(|e| e.is_free())?;
+                // find pending tasks that are not claimed by another node,
+                // in the order of the scheduling policy
+                let now = unix_time_ms();
+                let mut tasks: Vec<&ProofRequest> = rw
+                    .tasks
+                    .iter()
+                    .filter(|&e| {
+                        e.result.is_none()
+                            && !skip.contains(&e.options)
+                            && !rw
+                                .slots
+                                .iter()
+                                .any(|s| s.pending.as_ref() == Some(&e.options))
+                            && e.lease
+                                .as_ref()
+                                .map_or(true, |l| l.node_id == self.ro.node_id || l.expires <= now)
+                    })
+                    .collect();
+                tasks.sort_by(|a, b| cmp_task_priority(a, b));
+                // nothing to do?
+                let task = tasks.first()?.options.clone();
+
+                // signals that this node wants to process this task
+                log::debug!("trying to obtain slot={} {:#?}", slot_index, task);
+                let lease = TaskLease {
+                    node_id: self.ro.node_id.clone(),
+                    expires: now + self.ro.lease_duration.as_millis() as u64,
+                };
+                rw.slots[slot_index].pending = Some(task.clone());
+                let task_entry = rw.tasks.iter_mut().find(|e| e.options == task).unwrap();
+                task_entry.lease = Some(lease.clone());
+                (slot_index, task, lease)
+            };
 
             // notify other peers
             // wrap the object because it's important to clear `pending` on error
-            {
+            let obtain_task = {
                 let self_copy = self.clone();
-                let obtain_task =
-                    tokio::spawn(
-                        async move { self_copy.obtain_task().await.expect("obtain_task") },
-                    )
-                    .await;
+                let task = task.clone();
+                let lease = lease.clone();
+                tokio::spawn(async move { self_copy.obtain_task(&task, &lease).await }).await
+            };
+            let won = match obtain_task {
+                Err(err) => {
+                    log::error!("obtain_task: {}", join_error_to_string(err));
+                    false
+                }
+                Ok(Err(err)) => {
+                    log::error!("obtain_task: {}", err);
+                    false
+                }
+                Ok(Ok(val)) => val,
+            };
 
-                if obtain_task.is_err() || !obtain_task.unwrap() {
-                    self.rw.lock().await.pending = None;
-                    log::debug!("failed to obtain task");
-                    continue;
+            let mut rw = self.rw.lock().await;
+            if rw.slots[slot_index].pending.as_ref() != Some(&task) {
+                // the task was cancelled in the meantime
+                continue;
+            }
+            // a peer with a lower id may have claimed the task in the meantime
+            let task_entry = rw.tasks.iter_mut().find(|e| e.options == task);
+            let lease_kept = task_entry
+                .as_ref()
+                .map_or(false, |e| e.lease.as_ref() == Some(&lease));
+            if !won || !lease_kept {
+                log::debug!("failed to obtain task");
+                if lease_kept {
+                    task_entry.unwrap().lease = None;
                 }
+                rw.slots[slot_index].pending = None;
+                drop(rw);
 
-                // won the race
-                self.rw.lock().await.obtained = true;
-                break;
+                // not strictly required, stale claims expire eventually
+                self.release_claim(&task, &lease).await;
+                skip.push(task);
+                continue;
             }
-        }
 
-        // needs to be cloned because of long running tasks and
-        // the possibility that the task gets removed in the meantime
-        let task_options = self.rw.lock().await.pending.clone();
-        if task_options.is_none() {
-            // nothing to do
-            return;
+            // won the race
+            let (cancel_tx, cancel_rx) = oneshot::channel::<()>();
+            let slot = &mut rw.slots[slot_index];
+            slot.obtained = true;
+            slot.cancel = Some(cancel_tx);
+            log::info!("obtained slot={} {:#?}", slot_index, task);
+
+            return Some((slot_index, task, cancel_rx));
         }
+    }
 
-        // succesfully obtained the task
-        let task_options = task_options.unwrap();
-        log::info!("compute_proof: {:#?}", task_options);
+    /// Computes `task_options` in the obtained slot `slot_index`
+    /// and updates the queue with the result.
+    async fn run_slot(
+        &self,
+        slot_index: usize,
+        task_options: ProofRequestOptions,
+        cancel_rx: oneshot::Receiver<()>,
+    ) {
+        log::info!("compute_proof: slot={} {:#?}", slot_index, task_options);
 
         // Note: this catches any panics for the task itself but will not help in the
         // situation when the process get itself OOM killed, stack overflows etc.
-        // This could be avoided by spawning a subprocess for the proof computation
-        // instead.
+        // unless the computation is done in a `worker` subprocess.
 
         // spawn a task to catch panics
-        let task_result: Result<Result<Proofs, String>, tokio::task::JoinError> = {
+        let task_result: Option<Result<Result<Proofs, String>, tokio::task::JoinError>> = {
             let task_options_copy = task_options.clone();
             let self_copy = self.clone();
 
-            tokio::spawn(async move {
-                let witness =
-                    CircuitWitness::from_rpc(&task_options_copy.block, &task_options_copy.rpc)
-                        .await
-                        .map_err(|e| e.to_string())?;
-
-                let (config, circuit_proof, aggregation_proof) = crate::match_circuit_params!(
-                    witness.gas_used(),
-                    {
-                        match task_options_copy.circuit.as_str() {
-                            "pi" => {
-                                gen_proof!(self_copy, task_options_copy, &witness, gen_pi_circuit)
-                            }
-                            "super" => {
-                                gen_proof!(
-                                    self_copy,
-                                    task_options_copy,
-                                    &witness,
-                                    gen_super_circuit
-                                )
-                            }
-                            "evm" => {
-                                gen_proof!(self_copy, task_options_copy, &witness, gen_evm_circuit)
-                            }
-                            "state" => gen_proof!(
-                                self_copy,
-                                task_options_copy,
-                                &witness,
-                                gen_state_circuit
-                            ),
-                            "tx" => {
-                                gen_proof!(self_copy, task_options_copy, &witness, gen_tx_circuit)
-                            }
-                            "bytecode" => gen_proof!(
-                                self_copy,
-                                task_options_copy,
-                                &witness,
-                                gen_bytecode_circuit
-                            ),
-                            "copy" => {
-                                gen_proof!(self_copy, task_options_copy, &witness, gen_copy_circuit)
-                            }
-                            "exp" => {
-                                gen_proof!(self_copy, task_options_copy, &witness, gen_exp_circuit)
-                            }
-                            "keccak" => gen_proof!(
-                                self_copy,
-                                task_options_copy,
-                                &witness,
-                                gen_keccak_circuit
-                            ),
-                            _ => panic!("unknown circuit"),
-                        }
-                    },
-                    {
-                        return Err(format!(
-                            "No circuit parameters found for block with gas used={}",
-                            witness.gas_used()
-                        ));
-                    }
-                );
-
-                let res = Proofs {
-                    config,
-                    circuit: circuit_proof,
-                    aggregation: aggregation_proof,
-                    gas: witness.gas_used(),
-                };
-
-                Ok(res)
-            })
-            .await
+            let mut handle = tokio::spawn(async move {
+                match &self_copy.ro.worker {
+                    Some(worker) => worker.compute_proof(&task_options_copy).await,
+                    None => self_copy.compute_proof(&task_options_copy).await,
+                }
+            });
+
+            tokio::select! {
+                res = &mut handle => Some(res),
+                Ok(()) = cancel_rx => {
+                    // A `worker` subprocess gets killed once the task is dropped.
+                    // Otherwise, this only stops the task at the next `.await` point,
+                    // the current (blocking) proof computation keeps running in the background
+                    // and its result is discarded.
+                    handle.abort();
+                    None
+                }
+            }
         };
 
+        if task_result.is_none() {
+            // the slot was already cleared by whoever interrupted the task
+            log::info!("task was interrupted {:#?}", task_options);
+            return;
+        }
+
         // convert the JoinError to string - if applicable
-        let task_result: Result<Proofs, String> = match task_result {
-            Err(err) => match err.is_panic() {
-                true => {
-                    let panic = err.into_panic();
-
-                    if let Some(msg) = panic.downcast_ref::<&str>() {
-                        Err(msg.to_string())
-                    } else if let Some(msg) = panic.downcast_ref::<String>() {
-                        Err(msg.to_string())
-                    } else {
-                        Err("unknown panic".to_string())
-                    }
-                }
-                false => Err(err.to_string()),
-            },
+        let metrics = &self.ro.metrics;
+        let mut panicked = false;
+        let task_result: Result<Proofs, String> = match task_result.unwrap() {
+            Err(err) => {
+                panicked = true;
+                Err(join_error_to_string(err))
+            }
             Ok(val) => val,
         };
+        match &task_result {
+            Err(_) if panicked => metrics.failures.with_label_values(&["panic"]).inc(),
+            Err(err) => metrics.failures.with_label_values(&[error_kind(err)]).inc(),
+            Ok(proofs) => {
+                for proof in [&proofs.circuit, &proofs.aggregation] {
+                    if !proof.label.is_empty() && proof.duration > 0 {
+                        metrics
+                            .proof_duration
+                            .with_label_values(&[proof.label.as_str()])
+                            .observe(proof.duration as f64 / 1000.0);
+                    }
+                }
+            }
+        }
 
         {
             // done, update the queue
@@ -471,14 +709,18 @@ impl SharedState {
 
             let mut rw = self.rw.lock().await;
             // clear fields
-            rw.pending = None;
-            rw.obtained = false;
+            let slot = &mut rw.slots[slot_index];
+            if slot.pending.as_ref() == Some(&task_options) {
+                *slot = Slot::default();
+            }
             // insert task result
             let task = rw.tasks.iter_mut().find(|e| e.options == task_options);
             if let Some(task) = task {
                 // found our task, update result
                 task.result = Some(task_result);
                 task.edition += 1;
+                task.lease = None;
+                self.save_task(task);
             } else {
                 // task was already removed in the meantime,
                 // assume it's obsolete and forget about it
@@ -490,6 +732,231 @@ impl SharedState {
         }
     }
 
+    /// Computes the proofs for `task_options` in this process.
+    pub async fn compute_proof(
+        &self,
+        task_options: &ProofRequestOptions,
+    ) -> Result<Proofs, String> {
+        let to_block = task_options.to_block.unwrap_or(task_options.block);
+        if to_block < task_options.block {
+            return Err(format!(
+                "to_block={} is before block={}",
+                to_block, task_options.block
+            ));
+        }
+        if to_block > task_options.block && !task_options.aggregate && !task_options.mock {
+            return Err("proofs of multiple blocks require aggregate".to_string());
+        }
+        if to_block - task_options.block >= MAX_AGGREGATION_BLOCKS as u64 {
+            return Err(format!(
+                "proofs of more than {} blocks are not supported",
+                MAX_AGGREGATION_BLOCKS
+            ));
+        }
+
+        let mut witnesses = Vec::new();
+        for block in task_options.block..=to_block {
+            let witness = CircuitWitness::from_rpc(&block, &task_options.rpc)
+                .await
+                .map_err(|e| e.to_string())?;
+            witnesses.push(witness);
+        }
+        // the aggregation circuit depends on the circuit parameters of each block,
+        // only one verifier per parameter set is supported for now.
+        let block_gas_limits: Vec<Option<usize>> = witnesses
+            .iter()
+            .map(|witness| {
+                crate::match_circuit_params!(
+                    witness.gas_used(),
+                    Some(CIRCUIT_CONFIG.block_gas_limit),
+                    None
+                )
+            })
+            .collect();
+        if block_gas_limits.windows(2).any(|e| e[0] != e[1]) {
+            return Err(format!(
+                "blocks {}..={} use different circuit parameters",
+                task_options.block, to_block
+            ));
+        }
+
+        let mut res = Proofs::default();
+        let mut snarks = Vec::new();
+        for witness in witnesses.iter() {
+            let (config, circuit_proof, snark) =
+                self.compute_circuit_proof(task_options, witness).await?;
+            res.config = config;
+            // the aggregation proof of a range covers the circuit proofs of each block,
+            // only the proof of a single block is kept
+            if witnesses.len() == 1 {
+                res.circuit = circuit_proof;
+            }
+            res.gas = std::cmp::max(res.gas, witness.gas_used());
+            snarks.extend(snark);
+        }
+        if !snarks.is_empty() {
+            res.aggregation = self
+                .aggregate_proofs(task_options, &res.config, snarks)
+                .await?;
+        }
+
+        Ok(res)
+    }
+
+    /// Computes the circuit proof for a single block.
+    /// Returns the snark for the aggregation circuit if `task_options.aggregate` is set.
+    async fn compute_circuit_proof(
+        &self,
+        task_options: &ProofRequestOptions,
+        witness: &CircuitWitness,
+    ) -> Result<(CircuitConfig, ProofResult, Option<Snark>), String> {
+        let res = crate::match_circuit_params!(
+            witness.gas_used(),
+            {
+                match task_options.circuit.as_str() {
+                    "pi" => {
+                        gen_proof!(self, task_options, witness, gen_pi_circuit)
+                    }
+                    "super" => {
+                        gen_proof!(self, task_options, witness, gen_super_circuit)
+                    }
+                    "evm" => {
+                        gen_proof!(self, task_options, witness, gen_evm_circuit)
+                    }
+                    "state" => gen_proof!(self, task_options, witness, gen_state_circuit),
+                    "tx" => {
+                        gen_proof!(self, task_options, witness, gen_tx_circuit)
+                    }
+                    "bytecode" => gen_proof!(self, task_options, witness, gen_bytecode_circuit),
+                    "copy" => {
+                        gen_proof!(self, task_options, witness, gen_copy_circuit)
+                    }
+                    "exp" => {
+                        gen_proof!(self, task_options, witness, gen_exp_circuit)
+                    }
+                    "keccak" => gen_proof!(self, task_options, witness, gen_keccak_circuit),
+                    _ => panic!("unknown circuit"),
+                }
+            },
+            {
+                return Err(format!(
+                    "No circuit parameters found for block with gas used={}",
+                    witness.gas_used()
+                ));
+            }
+        );
+
+        Ok(res)
+    }
+
+    /// Aggregates the circuit proofs `snarks` of consecutive blocks into one proof.
+    /// A single snark keeps the label and keys of the single block aggregation circuit,
+    /// otherwise the number of snarks is part of both.
+    async fn aggregate_proofs(
+        &self,
+        task_options: &ProofRequestOptions,
+        circuit_config: &CircuitConfig,
+        snarks: Vec<Snark>,
+    ) -> Result<ProofResult, String> {
+        let suffix = match snarks.len() {
+            1 => String::new(),
+            n => n.to_string(),
+        };
+        let mut aggregation_proof = ProofResult::default();
+        aggregation_proof.label = format!(
+            "{}-{}-a{}",
+            task_options.circuit, circuit_config.block_gas_limit, suffix
+        );
+
+        let time_started = Instant::now();
+        let (agg_params, agg_param_path) =
+            get_or_gen_param(task_options, circuit_config.min_k_aggregation);
+        aggregation_proof.k = agg_params.k() as u8;
+        let agg_circuit = AggregationCircuit::new(agg_params.as_ref(), snarks, fixed_rng());
+        let agg_pk = {
+            let cache_key = format!(
+                "{}ag{}",
+                pk_cache_key(&task_options.circuit, &agg_param_path, circuit_config),
+                suffix
+            );
+            self.gen_pk(&cache_key, &agg_params, &agg_circuit)
+                .await
+                .map_err(|e| e.to_string())?
+        };
+        let agg_instance = agg_circuit.instance();
+        aggregation_proof.instance = collect_instance(&agg_instance);
+        let proof = gen_proof::<
+            _,
+            _,
+            EvmTranscript<G1Affine, _, _, _>,
+            EvmTranscript<G1Affine, _, _, _>,
+            _,
+        >(
+            agg_params.as_ref(),
+            &agg_pk,
+            agg_circuit,
+            agg_instance,
+            fixed_rng(),
+            task_options.mock_feedback,
+            task_options.verify_proof,
+        );
+        aggregation_proof.duration = Instant::now().duration_since(time_started).as_millis() as u32;
+        aggregation_proof.proof = proof.into();
+
+        Ok(aggregation_proof)
+    }
+
+    /// Cancels the task for `options` if it's not completed yet.
+    /// The task is removed from the queue and a slot working on it gets cleared.
+    /// The cancellation is forwarded to all peers, which remove the task as well
+    /// and don't merge it again.
+    ///
+    /// Note: A `worker` subprocess computing the task gets killed. Otherwise, this is a
+    /// soft cancel, the proof computation keeps running in the background
+    /// until it finishes and its result is discarded.
+    ///
+    /// Returns `false` if there is no such task or if it's already completed.
+    pub async fn cancel(&self, options: &ProofRequestOptions) -> bool {
+        {
+            let mut rw = self.rw.lock().await;
+            let index = rw
+                .tasks
+                .iter()
+                .position(|e| e.options == *options && e.result.is_none());
+
+            if index.is_none() {
+                return false;
+            }
+
+            let task = rw.tasks.remove(index.unwrap());
+            self.ro
+                .metrics
+                .failures
+                .with_label_values(&["cancelled"])
+                .inc();
+            log::info!("cancelled: {:#?}", task);
+            self.remove_task(&task.options);
+
+            rw.interrupt_pending(options);
+            rw.cancelled_tasks.push_back(task.options);
+            if rw.cancelled_tasks.len() > CANCELLED_TASKS_HISTORY {
+                rw.cancelled_tasks.pop_front();
+            }
+        }
+
+        // peers that already removed the task don't forward it again
+        let hyper_client = hyper::Client::new();
+        if let Ok(addrs) = self.peer_addrs().await {
+            for addr in addrs {
+                let _: Result<bool, String> = self
+                    .peer_request(&hyper_client, &addr, "cancel", [options])
+                    .await;
+            }
+        }
+
+        true
+    }
+
     /// Returns `node_id` and `tasks` for this instance.
     /// Normally used for the rpc api.
     pub async fn get_node_information(&self) -> NodeInformation {
@@ -508,24 +975,31 @@ impl SharedState {
     pub async fn merge_tasks_from_peers(&self) -> Result<bool, String> {
         const LOG_TAG: &str = "merge_tasks_from_peers:";
 
-        if self.ro.node_lookup.is_none() {
-            return Ok(true);
-        }
+        // heartbeat
+        self.renew_leases().await;
 
         let hyper_client = hyper::Client::new();
-        let addrs_iter = self
-            .ro
-            .node_lookup
-            .as_ref()
-            .unwrap()
-            .to_socket_addrs()
-            .map_err(|e| e.to_string())?;
-
-        for addr in addrs_iter {
-            let uri = Uri::try_from(format!("http://{}", addr)).map_err(|e| e.to_string())?;
-            let peer: NodeInformation =
-                jsonrpc_request_client(5000, &hyper_client, &uri, "info", serde_json::json!([]))
-                    .await?;
+        let addrs = self.peer_addrs().await?;
+        self.rw
+            .lock()
+            .await
+            .unreachable_peers
+            .retain(|addr, _| addrs.contains(addr));
+        for addr in addrs {
+            let time_started = Instant::now();
+            let peer: Result<NodeInformation, String> = self
+                .peer_request(&hyper_client, &addr, "info", serde_json::json!([]))
+                .await;
+            self.track_peer(&addr, peer.is_ok()).await;
+            // an unreachable peer doesn't prevent merging with the others
+            let peer = match peer {
+                Err(err) => {
+                    log::warn!("{} skipping peer({}): {}", LOG_TAG, addr, err);
+                    self.ro.metrics.merge_failures.inc();
+                    continue;
+                }
+                Ok(val) => val,
+            };
 
             if peer.id == self.ro.node_id {
                 log::debug!("{} skipping self({})", LOG_TAG, peer.id);
@@ -534,12 +1008,17 @@ impl SharedState {
 
             log::debug!("{} merging with peer({})", LOG_TAG, peer.id);
             self.merge_tasks(&peer).await;
+            self.ro
+                .metrics
+                .merge_duration
+                .observe(Instant::now().duration_since(time_started).as_secs_f64());
         }
 
         Ok(true)
     }
 
-    // TODO: can this be pre-generated to a file?
+    // TODO: pre-generate the keys to a file once halo2 can serialize the `ProvingKey`,
+    // storing only the `VerifyingKey` still requires `keygen_pk` on every start.
     // related
     // https://github.com/zcash/halo2/issues/443
     // https://github.com/zcash/halo2/issues/449
@@ -550,23 +1029,37 @@ impl SharedState {
         param: &Arc<ProverParams>,
         circuit: &C,
     ) -> Result<Arc<ProverKey>, Box<dyn std::error::Error>> {
-        let mut rw = self.rw.lock().await;
-        if !rw.pk_cache.contains_key(cache_key) {
-            // drop, potentially long running
-            drop(rw);
+        let cell = self
+            .rw
+            .lock()
+            .await
+            .pk_cache
+            .entry(cache_key.to_string())
+            .or_default()
+            .clone();
 
-            let vk = keygen_vk(param.as_ref(), circuit)?;
-            let pk = keygen_pk(param.as_ref(), vk, circuit)?;
-            let pk = Arc::new(pk);
+        let result = if cell.initialized() { "hit" } else { "miss" };
+        self.ro.metrics.pk_cache.with_label_values(&[result]).inc();
 
-            // acquire lock and update
-            rw = self.rw.lock().await;
-            rw.pk_cache.insert(cache_key.to_string(), pk);
+        // concurrent callers for the same key wait for the first one
+        // instead of generating the key again
+        let pk = cell
+            .get_or_try_init(|| async {
+                let time_started = Instant::now();
 
-            log::info!("ProvingKey: generated and cached key={}", cache_key);
-        }
+                let vk = keygen_vk(param.as_ref(), circuit)?;
+                let pk = keygen_pk(param.as_ref(), vk, circuit)?;
+                self.ro
+                    .metrics
+                    .keygen_duration
+                    .observe(Instant::now().duration_since(time_started).as_secs_f64());
+                log::info!("ProvingKey: generated and cached key={}", cache_key);
 
-        Ok(rw.pk_cache.get(cache_key).unwrap().clone())
+                Ok::<_, Box<dyn std::error::Error>>(Arc::new(pk))
+            })
+            .await?;
+
+        Ok(pk.clone())
     }
 
     async fn merge_tasks(&self, node_info: &NodeInformation) {
@@ -574,9 +1067,25 @@ impl SharedState {
         let mut rw = self.rw.lock().await;
 
         for peer_task in &node_info.tasks {
+            let obtained = rw
+                .slots
+                .iter()
+                .any(|e| e.obtained && e.pending.as_ref() == Some(&peer_task.options));
             let maybe_task = rw.tasks.iter_mut().find(|e| e.options == peer_task.options);
 
             if let Some(existent_task) = maybe_task {
+                // leases are independent of `edition`, the later expiry wins.
+                // the lease of a task this instance is working on stays untouched.
+                if let Some(peer_lease) = &peer_task.lease {
+                    let newer = existent_task
+                        .lease
+                        .as_ref()
+                        .map_or(true, |e| peer_lease.expires > e.expires);
+                    if newer && !obtained {
+                        existent_task.lease = Some(peer_lease.clone());
+                    }
+                }
+
                 if existent_task.edition >= peer_task.edition {
                     // fast case
                     log::debug!("{} up to date {:#?}", LOG_TAG, existent_task);
@@ -587,73 +1096,194 @@ impl SharedState {
                 existent_task.edition = peer_task.edition;
                 existent_task.result = peer_task.result.clone();
                 log::debug!("{} updated {:#?}", LOG_TAG, existent_task);
+                self.save_task(existent_task);
+
+                // the task was completed elsewhere
+                if existent_task.result.is_some() {
+                    log::debug!("{} interrupting {:#?}", LOG_TAG, peer_task.options);
+                    rw.interrupt_pending(&peer_task.options);
+                }
+            } else if rw.cancelled_tasks.contains(&peer_task.options) {
+                // the peer didn't learn about the cancellation yet
+                log::debug!("{} skipping cancelled {:#?}", LOG_TAG, peer_task.options);
+                continue;
             } else {
                 // copy task
+                self.save_task(peer_task);
                 rw.tasks.push(peer_task.clone());
                 log::debug!("{} new task {:#?}", LOG_TAG, peer_task);
             }
         }
     }
 
-    /// Tries to obtain `self.rw.pending` by querying all other peers
-    /// about their current task item that resolves to either
-    /// winning or losing the task depending on the algorithm.
+    /// Tries to obtain `task_options` by sending the claim `lease` to all peers.
+    /// The task is obtained if every peer accepted the claim, see `accept_claim`.
+    /// A peer that doesn't respond counts as a rejection, unless every request to it
+    /// failed for longer than `lease_duration`. Such a peer can't hold a valid lease,
+    /// because it neither renewed its leases nor obtained a task in the meantime.
     ///
-    /// Expects `self.rw.pending` to be not `None`
-    async fn obtain_task(&self) -> Result<bool, String> {
+    /// Expects a slot of `self.rw` to be pending on `task_options`
+    /// and `lease` to be the current lease of the task.
+    async fn obtain_task(
+        &self,
+        task_options: &ProofRequestOptions,
+        lease: &TaskLease,
+    ) -> Result<bool, String> {
         const LOG_TAG: &str = "obtain_task:";
 
-        let task_options = self
-            .rw
-            .lock()
-            .await
-            .pending
-            .as_ref()
-            .expect("pending task")
-            .clone();
+        // resolve all other nodes for this service
+        let hyper_client = hyper::Client::new();
+        for addr in self.peer_addrs().await? {
+            // this instance may be part of the lookup as well,
+            // accepts its own claim as long as the lease wasn't taken over.
+            let accepted: Result<bool, String> = self
+                .peer_request(
+                    &hyper_client,
+                    &addr,
+                    "claim",
+                    serde_json::json!([task_options, lease]),
+                )
+                .await;
+            let unreachable_for = self.track_peer(&addr, accepted.is_ok()).await;
+            let accepted = match accepted {
+                Err(err) if unreachable_for > self.ro.lease_duration.as_millis() as u64 => {
+                    log::warn!(
+                        "{} skipping peer({}), unreachable for {}ms: {}",
+                        LOG_TAG,
+                        addr,
+                        unreachable_for,
+                        err
+                    );
+                    continue;
+                }
+                Err(err) => {
+                    log::warn!("{} claim failed for peer({}): {}", LOG_TAG, addr, err);
+                    return Ok(false);
+                }
+                Ok(val) => val,
+            };
 
-        if self.ro.node_lookup.is_none() {
-            return Ok(true);
+            if !accepted {
+                log::debug!("{} claim rejected by {}", LOG_TAG, addr);
+                // early return
+                return Ok(false);
+            }
         }
 
-        // resolve all other nodes for this service
+        // default
+        Ok(true)
+    }
+
+    /// Records whether the last request to the peer at `addr` succeeded.
+    /// Returns the number of milliseconds since every request to it failed.
+    async fn track_peer(&self, addr: &SocketAddr, reachable: bool) -> u64 {
+        let mut rw = self.rw.lock().await;
+        if reachable {
+            rw.unreachable_peers.remove(addr);
+            return 0;
+        }
+
+        let now = unix_time_ms();
+        let since = *rw.unreachable_peers.entry(*addr).or_insert(now);
+        now.saturating_sub(since)
+    }
+
+    /// Withdraws the claim `lease` from all peers, errors are ignored.
+    async fn release_claim(&self, task_options: &ProofRequestOptions, lease: &TaskLease) {
         let hyper_client = hyper::Client::new();
-        let addrs_iter = self
-            .ro
-            .node_lookup
-            .as_ref()
-            .unwrap()
-            .to_socket_addrs()
-            .map_err(|e| e.to_string())?;
-        for addr in addrs_iter {
-            let uri = Uri::try_from(format!("http://{}", addr)).map_err(|e| e.to_string())?;
-            let peer: NodeStatus =
-                jsonrpc_request_client(5000, &hyper_client, &uri, "status", serde_json::json!([]))
-                    .await?;
+        let addrs = match self.peer_addrs().await {
+            Err(_) => return,
+            Ok(val) => val,
+        };
+        for addr in addrs {
+            let _: Result<bool, String> = self
+                .peer_request(
+                    &hyper_client,
+                    &addr,
+                    "release",
+                    serde_json::json!([task_options, lease]),
+                )
+                .await;
+        }
+    }
 
-            if peer.id == self.ro.node_id {
-                log::debug!("{} skipping self({})", LOG_TAG, peer.id);
-                continue;
+    /// Handles the claim `lease` of a peer for the task `options`.
+    /// The claim gets recorded and accepted unless:
+    /// - this instance already obtained the task
+    /// - the task is completed
+    /// - the task has a valid lease of a node with a lower id
+    ///
+    /// Unknown tasks are accepted, because this instance can't compete for them.
+    /// Every claimer asks all peers - including the other claimers - for approval
+    /// and a claimer only obtains the task if its own lease is still intact afterwards.
+    /// That leaves at most one winner among concurrent claims.
+    pub async fn accept_claim(&self, options: &ProofRequestOptions, lease: &TaskLease) -> bool {
+        let mut rw = self.rw.lock().await;
+
+        if rw
+            .slots
+            .iter()
+            .any(|e| e.obtained && e.pending.as_ref() == Some(options))
+        {
+            return lease.node_id == self.ro.node_id;
+        }
+
+        let now = unix_time_ms();
+        let task = match rw.tasks.iter_mut().find(|e| e.options == *options) {
+            None => return true,
+            Some(val) => val,
+        };
+        if task.result.is_some() {
+            return false;
+        }
+        if let Some(current) = &task.lease {
+            if current.node_id != lease.node_id
+                && current.expires > now
+                && current.node_id < lease.node_id
+            {
+                return false;
             }
+        }
 
-            if let Some(peer_task) = peer.task {
-                if peer_task == task_options {
-                    // a slight chance to 'win' the task
-                    if !peer.obtained && peer.id > self.ro.node_id {
-                        log::debug!("{} won task against {}", LOG_TAG, peer.id);
-                        // continue the race against the remaining peers
-                        continue;
-                    }
+        task.lease = Some(lease.clone());
+        true
+    }
 
-                    log::debug!("{} lost task against {}", LOG_TAG, peer.id);
-                    // early return
-                    return Ok(false);
-                }
+    /// Removes the claim `lease` for the task `options` if it's still recorded.
+    pub async fn release(&self, options: &ProofRequestOptions, lease: &TaskLease) -> bool {
+        let mut rw = self.rw.lock().await;
+        let task = rw
+            .tasks
+            .iter_mut()
+            .find(|e| e.options == *options && e.lease.as_ref() == Some(lease));
+
+        match task {
+            None => false,
+            Some(task) => {
+                task.lease = None;
+                true
             }
         }
+    }
 
-        // default
-        Ok(true)
+    /// Extends the leases of all tasks this instance is working on.
+    /// Peers learn about it via `merge_tasks_from_peers`.
+    async fn renew_leases(&self) {
+        let mut rw = self.rw.lock().await;
+        let expires = unix_time_ms() + self.ro.lease_duration.as_millis() as u64;
+        let RwState { tasks, slots, .. } = &mut *rw;
+
+        for slot in slots.iter().filter(|e| e.obtained) {
+            let task = tasks
+                .iter_mut()
+                .find(|e| Some(&e.options) == slot.pending.as_ref());
+            if let Some(task) = task {
+                task.lease = Some(TaskLease {
+                    node_id: self.ro.node_id.clone(),
+                    expires,
+                });
+            }
+        }
     }
 
     pub fn random_worker_id() -> String {
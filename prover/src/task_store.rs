@@ -0,0 +1,125 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use zkevm_common::prover::ProofRequest;
+use zkevm_common::prover::ProofRequestOptions;
+
+/// A persistence backend for the task queue of proverd.
+pub trait TaskStore: Send + Sync {
+    /// Returns all tasks written by previous calls to `save`.
+    fn load(&self) -> Result<Vec<ProofRequest>, String>;
+    /// Inserts `task` or replaces the stored task with the same options.
+    fn save(&self, task: &ProofRequest) -> Result<(), String>;
+    /// Removes the stored task for `options`, if any.
+    fn remove(&self, options: &ProofRequestOptions) -> Result<(), String>;
+}
+
+/// A change of the task queue, see `SharedState::save_task`.
+pub enum TaskChange {
+    Save(ProofRequest),
+    Remove(ProofRequestOptions),
+}
+
+impl TaskChange {
+    pub fn apply(&self, task_store: &dyn TaskStore) -> Result<(), String> {
+        match self {
+            Self::Save(task) => task_store.save(task),
+            Self::Remove(options) => task_store.remove(options),
+        }
+    }
+}
+
+/// Stores each task as a json file inside a directory.
+pub struct FileTaskStore {
+    dir: PathBuf,
+}
+
+impl FileTaskStore {
+    /// Creates `dir` if it doesn't exist yet.
+    pub fn new(dir: &str) -> Result<Self, String> {
+        let dir = PathBuf::from(dir).join("tasks");
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+        Ok(Self { dir })
+    }
+
+    /// The file of the task with `options`,
+    /// named after a hash of the fields that identify the task.
+    fn task_path(&self, options: &ProofRequestOptions) -> PathBuf {
+        let key = serde_json::json!([
+            options.circuit,
+            options.block,
+            options.to_block,
+            options.rpc,
+            options.param,
+            options.mock,
+            options.aggregate,
+        ]);
+        // FNV-1a, stable across releases unlike `DefaultHasher`
+        let hash = key
+            .to_string()
+            .bytes()
+            .fold(0xcbf29ce484222325u64, |acc, e| {
+                (acc ^ e as u64).wrapping_mul(0x100000001b3)
+            });
+
+        self.dir.join(format!("{:016x}.json", hash))
+    }
+
+    /// Moves the tasks of the single file used by previous versions into `dir`.
+    fn migrate(&self) -> Result<(), String> {
+        let path = self.dir.with_file_name("tasks.json");
+        let file = match File::open(&path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.to_string()),
+            Ok(file) => file,
+        };
+        let tasks: Vec<ProofRequest> =
+            serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string())?;
+        for task in tasks.iter() {
+            self.save(task)?;
+        }
+
+        std::fs::remove_file(&path).map_err(|e| e.to_string())
+    }
+}
+
+impl TaskStore for FileTaskStore {
+    fn load(&self) -> Result<Vec<ProofRequest>, String> {
+        self.migrate()?;
+
+        let mut tasks = Vec::new();
+        for entry in std::fs::read_dir(&self.dir).map_err(|e| e.to_string())? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            // skips the temporary files of interrupted writes
+            if path.extension().map_or(true, |e| e != "json") {
+                continue;
+            }
+            let file = File::open(&path).map_err(|e| e.to_string())?;
+            let task: ProofRequest = serde_json::from_reader(BufReader::new(file))
+                .map_err(|e| format!("{:?}: {}", path, e))?;
+            tasks.push(task);
+        }
+
+        Ok(tasks)
+    }
+
+    fn save(&self, task: &ProofRequest) -> Result<(), String> {
+        // write to a temporary file first and replace the old file afterwards,
+        // a crash in between leaves the previous version intact.
+        let path = self.task_path(&task.options);
+        let tmp_path = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp_path).map_err(|e| e.to_string())?;
+        serde_json::to_writer(&mut file, task).map_err(|e| e.to_string())?;
+        file.sync_all().map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+    }
+
+    fn remove(&self, options: &ProofRequestOptions) -> Result<(), String> {
+        match std::fs::remove_file(self.task_path(options)) {
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            res => res.map_err(|e| e.to_string()),
+        }
+    }
+}
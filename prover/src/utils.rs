@@ -23,6 +23,7 @@ use zkevm_circuits::tx_circuit::PrimeField;
 use rand::SeedableRng;
 use std::clone::Clone;
 use std::io::Cursor;
+use std::time::SystemTime;
 
 /// Returns [<len>, ...] of `instance`
 pub fn gen_num_instance(instance: &[Vec<Fr>]) -> Vec<usize> {
@@ -87,6 +88,14 @@ pub fn gen_proof<
     proof
 }
 
+/// Returns the current unix timestamp in milliseconds.
+pub fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("time")
+        .as_millis() as u64
+}
+
 /// Fixed rng for testing purposes
 pub fn fixed_rng() -> StdRng {
     StdRng::seed_from_u64(9)
@@ -100,3 +109,21 @@ pub fn collect_instance(instance: &[Vec<Fr>]) -> Vec<U256> {
         .map(|v| U256::from_little_endian(v.to_repr().as_ref()))
         .collect()
 }
+
+/// Returns the panic message or the error description of `err`.
+pub fn join_error_to_string(err: tokio::task::JoinError) -> String {
+    match err.is_panic() {
+        true => {
+            let panic = err.into_panic();
+
+            if let Some(msg) = panic.downcast_ref::<&str>() {
+                msg.to_string()
+            } else if let Some(msg) = panic.downcast_ref::<String>() {
+                msg.to_string()
+            } else {
+                "unknown panic".to_string()
+            }
+        }
+        false => err.to_string(),
+    }
+}
@@ -0,0 +1,172 @@
+use crate::shared_state::SharedState;
+use crate::utils::join_error_to_string;
+use std::os::unix::process::ExitStatusExt;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::process::Child;
+use tokio::process::ChildStdin;
+use tokio::process::ChildStdout;
+use tokio::process::Command;
+use zkevm_common::prover::*;
+
+/// Configuration for computing proofs in child processes
+/// via the worker mode of the `prover_cmd` binary.
+/// A child process reads one `ProofRequestOptions` per line as json from stdin
+/// and writes one `Result<Proofs, String>` per line as json to stdout.
+/// It keeps running between proofs, thus proving keys are only generated once per process.
+#[derive(Clone, Debug)]
+pub struct WorkerConfig {
+    /// Path to the `prover_cmd` binary.
+    pub cmd: String,
+    /// Limits the virtual memory of the child process, in bytes.
+    pub memory_limit: Option<u64>,
+    /// The child processes that wait for the next proof.
+    idle: Arc<Mutex<Vec<WorkerProcess>>>,
+}
+
+/// A running child process of `WorkerConfig`.
+#[derive(Debug)]
+struct WorkerProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl WorkerProcess {
+    /// Sends `task_options` to the child process and waits for the result.
+    /// Fails if the child process terminated.
+    async fn compute_proof(
+        &mut self,
+        task_options: &ProofRequestOptions,
+    ) -> Result<Result<Proofs, String>, String> {
+        let mut input = serde_json::to_vec(task_options).map_err(|e| e.to_string())?;
+        input.push(b'\n');
+        self.stdin
+            .write_all(&input)
+            .await
+            .map_err(|e| format!("worker: stdin: {}", e))?;
+        self.stdin
+            .flush()
+            .await
+            .map_err(|e| format!("worker: stdin: {}", e))?;
+
+        let mut line = String::new();
+        let len = self
+            .stdout
+            .read_line(&mut line)
+            .await
+            .map_err(|e| format!("worker: stdout: {}", e))?;
+        if len == 0 {
+            // OOM kills, stack overflows and the like end up here
+            let status = self
+                .child
+                .wait()
+                .await
+                .map_err(|e| format!("worker: {}", e))?;
+            return Err(match status.signal() {
+                Some(signal) => format!("worker: terminated by signal {}", signal),
+                None => format!("worker: {}", status),
+            });
+        }
+
+        serde_json::from_str::<Result<Proofs, String>>(&line)
+            .map_err(|e| format!("worker: invalid output: {}", e))
+    }
+}
+
+impl WorkerConfig {
+    pub fn new(cmd: String, memory_limit: Option<u64>) -> Self {
+        Self {
+            cmd,
+            memory_limit,
+            idle: Arc::default(),
+        }
+    }
+
+    /// Computes the proof in an idle child process or spawns a new one.
+    /// The child process gets killed if the returned future is dropped
+    /// or if the child process fails, otherwise it's reused for the next proof.
+    pub async fn compute_proof(
+        &self,
+        task_options: &ProofRequestOptions,
+    ) -> Result<Proofs, String> {
+        let idle = self.idle.lock().unwrap().pop();
+        let mut worker = match idle {
+            Some(worker) => worker,
+            None => self.spawn()?,
+        };
+
+        let result = worker.compute_proof(task_options).await?;
+        self.idle.lock().unwrap().push(worker);
+
+        result
+    }
+
+    fn spawn(&self) -> Result<WorkerProcess, String> {
+        let mut cmd = Command::new(&self.cmd);
+        cmd.arg("worker")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true);
+
+        if let Some(memory_limit) = self.memory_limit {
+            // Safety: only invokes `setrlimit` in the child process
+            unsafe {
+                cmd.pre_exec(move || {
+                    let limit = libc::rlimit {
+                        rlim_cur: memory_limit as libc::rlim_t,
+                        rlim_max: memory_limit as libc::rlim_t,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("worker: spawn {}: {}", self.cmd, e))?;
+        log::debug!("worker: spawned pid={:?}", child.id());
+
+        Ok(WorkerProcess {
+            stdin: child.stdin.take().expect("stdin"),
+            stdout: BufReader::new(child.stdout.take().expect("stdout")),
+            child,
+        })
+    }
+}
+
+/// The child process side of `WorkerConfig::compute_proof`.
+/// Reads `ProofRequestOptions` lines from stdin and writes a result line
+/// to stdout for each, until stdin is closed.
+pub async fn run_worker() {
+    let state = SharedState::new(String::new(), None);
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await.expect("read stdin") {
+        let task_options: ProofRequestOptions =
+            serde_json::from_str(&line).expect("ProofRequestOptions");
+        log::info!("worker: compute_proof: {:#?}", task_options);
+
+        // spawn a task to catch panics
+        let state = state.clone();
+        let result: Result<Proofs, String> =
+            match tokio::spawn(async move { state.compute_proof(&task_options).await }).await {
+                Err(err) => Err(join_error_to_string(err)),
+                Ok(val) => val,
+            };
+
+        let mut output = serde_json::to_vec(&result).expect("serialize");
+        output.push(b'\n');
+        stdout.write_all(&output).await.expect("write stdout");
+        stdout.flush().await.expect("flush stdout");
+    }
+}
@@ -413,6 +413,28 @@ macro_rules! match_circuit_params {{
         .write_all(str.as_bytes())
         .expect("write circuit_autogen.rs");
 
+    // generate `CIRCUIT_CONFIGS` for the coordinator
+    let mut str = String::new();
+    for config in params.values() {
+        write!(str, "{:#?},\n", config).expect("fmt write");
+    }
+
+    let str = format!(
+        "use crate::prover::CircuitConfig;
+
+/// The circuit parameters of the prover, ordered by `block_gas_limit`.
+pub const CIRCUIT_CONFIGS: [CircuitConfig; {}] = [
+{}];
+",
+        params.len(),
+        str
+    );
+
+    File::create("../common/src/circuit_autogen.rs")
+        .expect("create common circuit_autogen.rs")
+        .write_all(str.as_bytes())
+        .expect("write common circuit_autogen.rs");
+
     let mut str = String::new();
     for config in params.values() {
         write!(
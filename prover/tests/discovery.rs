@@ -0,0 +1,125 @@
+use prover::discovery::parse_srv_response;
+use prover::discovery::SrvRecord;
+
+const NAME: &str = "_rpc._tcp.proverd.local";
+
+fn encode_name(name: &str) -> Vec<u8> {
+    let mut ret = Vec::new();
+    for label in name.split('.') {
+        ret.push(label.len() as u8);
+        ret.extend_from_slice(label.as_bytes());
+    }
+    ret.push(0);
+
+    ret
+}
+
+/// A response for `NAME` with one SRV record per entry of `records`.
+/// The owner names point to the question.
+fn response(flags: u16, records: &[(u16, u16, u16, &str)]) -> Vec<u8> {
+    let mut ret = Vec::new();
+    ret.extend_from_slice(&0x1234u16.to_be_bytes());
+    ret.extend_from_slice(&flags.to_be_bytes());
+    ret.extend_from_slice(&1u16.to_be_bytes());
+    ret.extend_from_slice(&(records.len() as u16).to_be_bytes());
+    ret.extend_from_slice(&[0, 0, 0, 0]);
+    ret.extend_from_slice(&encode_name(NAME));
+    ret.extend_from_slice(&[0, 33, 0, 1]);
+
+    for (priority, weight, port, target) in records {
+        let target = encode_name(target);
+        // pointer to the question name
+        ret.extend_from_slice(&[0xc0, 12]);
+        // type, class, ttl
+        ret.extend_from_slice(&[0, 33, 0, 1, 0, 0, 0, 60]);
+        ret.extend_from_slice(&(6 + target.len() as u16).to_be_bytes());
+        ret.extend_from_slice(&priority.to_be_bytes());
+        ret.extend_from_slice(&weight.to_be_bytes());
+        ret.extend_from_slice(&port.to_be_bytes());
+        ret.extend_from_slice(&target);
+    }
+
+    ret
+}
+
+#[test]
+fn srv_response() {
+    let records = parse_srv_response(&response(
+        0x8180,
+        &[
+            (10, 5, 8545, "a.proverd.local"),
+            (0, 0, 8546, "b.proverd.local"),
+        ],
+    ))
+    .expect("parse");
+
+    assert_eq!(
+        records,
+        vec![
+            SrvRecord {
+                priority: 10,
+                weight: 5,
+                port: 8545,
+                target: "a.proverd.local".to_string(),
+            },
+            SrvRecord {
+                priority: 0,
+                weight: 0,
+                port: 8546,
+                target: "b.proverd.local".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn srv_record_order() {
+    let record = |priority: u16, weight: u16, target: &str| SrvRecord {
+        priority,
+        weight,
+        port: 8545,
+        target: target.to_string(),
+    };
+    let mut records = vec![
+        record(20, 100, "d"),
+        record(10, 0, "c"),
+        record(10, 50, "b"),
+        record(0, 0, "a"),
+    ];
+    records.sort();
+
+    let targets: Vec<&str> = records.iter().map(|e| e.target.as_str()).collect();
+    assert_eq!(targets, vec!["a", "b", "c", "d"]);
+}
+
+#[test]
+fn srv_response_errors() {
+    let valid = response(0x8180, &[(0, 0, 8545, "a.proverd.local")]);
+    // the offset of the owner name of the answer
+    let answer = 12 + encode_name(NAME).len() + 4;
+
+    // truncated flag
+    assert!(parse_srv_response(&response(0x8380, &[])).is_err());
+    // response code
+    assert!(parse_srv_response(&response(0x8183, &[])).is_err());
+
+    // cut off messages
+    for len in 0..valid.len() {
+        assert!(parse_srv_response(&valid[..len]).is_err(), "len={}", len);
+    }
+
+    // pointer loop
+    let mut msg = valid.clone();
+    msg[answer..answer + 2].copy_from_slice(&[0xc0, answer as u8]);
+    assert!(parse_srv_response(&msg).is_err());
+
+    // out of bounds pointer
+    let mut msg = valid.clone();
+    msg[answer..answer + 2].copy_from_slice(&[0xff, 0xff]);
+    assert!(parse_srv_response(&msg).is_err());
+
+    // rdlength past the end of the message
+    let mut msg = valid;
+    msg[answer + 10..answer + 12].copy_from_slice(&0xffffu16.to_be_bytes());
+    assert!(parse_srv_response(&msg).is_err());
+}
@@ -1,6 +1,14 @@
+use prover::discovery::StaticDiscovery;
 use prover::server::serve;
+use prover::shared_state::cmp_task_priority;
 use prover::shared_state::SharedState;
+use prover::task_store::FileTaskStore;
+use prover::task_store::TaskStore;
+use prover::utils::unix_time_ms;
+use std::sync::Arc;
 use tokio::time::{sleep, Duration};
+use zkevm_common::auth::AuthConfig;
+use zkevm_common::json_rpc::jsonrpc_call_with_token;
 use zkevm_common::prover::*;
 
 fn init_logger() {
@@ -68,3 +76,421 @@ async fn proverd_simple_signaling() {
     // check again
     assert!(node_a.get_or_enqueue(&proof_b).await.is_some());
 }
+
+#[tokio::test]
+async fn proverd_task_store() {
+    init_logger();
+
+    let data_dir = std::env::temp_dir().join(format!(
+        "proverd-task-store-{}",
+        SharedState::random_worker_id()
+    ));
+    let data_dir = data_dir.to_str().unwrap();
+    let proof = ProofRequestOptions {
+        circuit: "super".to_string(),
+        block: 1,
+        retry: false,
+        rpc: "http://localhost:1111".to_string(),
+        ..Default::default()
+    };
+
+    {
+        let task_store = FileTaskStore::new(data_dir).expect("task store");
+        let node = SharedState::new("a".to_string(), None)
+            .with_task_store(Arc::new(task_store))
+            .await
+            .expect("with_task_store");
+        assert!(node.get_or_enqueue(&proof).await.is_none());
+        node.duty_cycle().await;
+        assert!(node.get_or_enqueue(&proof).await.is_some());
+
+        // the task store is written in the background
+        let task_store = FileTaskStore::new(data_dir).expect("task store");
+        let mut retries = 0;
+        while !task_store
+            .load()
+            .expect("load")
+            .first()
+            .map_or(false, |e| e.result.is_some())
+        {
+            assert!(retries < 100, "task store not written");
+            retries += 1;
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    // a fresh instance should pick up the completed task
+    let task_store = FileTaskStore::new(data_dir).expect("task store");
+    let node = SharedState::new("a".to_string(), None)
+        .with_task_store(Arc::new(task_store))
+        .await
+        .expect("with_task_store");
+    assert!(node.get_or_enqueue(&proof).await.is_some());
+    assert_eq!(node.rw.lock().await.tasks[0].edition, 1);
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[tokio::test]
+async fn proverd_slots() {
+    init_logger();
+
+    let node = SharedState::new("a".to_string(), None).with_slots(2).await;
+    let proof = |block: u64| ProofRequestOptions {
+        circuit: "super".to_string(),
+        block,
+        retry: false,
+        rpc: "http://localhost:1111".to_string(),
+        ..Default::default()
+    };
+
+    for block in 1..=3 {
+        assert!(node.get_or_enqueue(&proof(block)).await.is_none());
+    }
+    // computes two tasks at once
+    node.duty_cycle().await;
+    assert!(node.get_or_enqueue(&proof(1)).await.is_some());
+    assert!(node.get_or_enqueue(&proof(2)).await.is_some());
+    assert!(node.get_or_enqueue(&proof(3)).await.is_none());
+    assert!(node
+        .rw
+        .lock()
+        .await
+        .slots
+        .iter()
+        .all(|e| e.pending.is_none()));
+
+    node.duty_cycle().await;
+    assert!(node.get_or_enqueue(&proof(3)).await.is_some());
+}
+
+#[tokio::test]
+async fn proverd_cancel() {
+    init_logger();
+
+    let node = SharedState::new("a".to_string(), None);
+    let proof = ProofRequestOptions {
+        circuit: "super".to_string(),
+        block: 1,
+        retry: false,
+        rpc: "http://localhost:1111".to_string(),
+        ..Default::default()
+    };
+
+    assert!(!node.cancel(&proof).await);
+    assert!(node.get_or_enqueue(&proof).await.is_none());
+    assert!(node.cancel(&proof).await);
+
+    // the task is removed and not picked up anymore
+    assert!(node.rw.lock().await.tasks.is_empty());
+    assert_eq!(node.rw.lock().await.cancelled_tasks.len(), 1);
+    node.duty_cycle().await;
+    assert!(node.rw.lock().await.tasks.is_empty());
+    assert!(!node.cancel(&proof).await);
+
+    // can be requested again
+    assert!(node.get_or_enqueue(&proof).await.is_none());
+    assert!(node.rw.lock().await.cancelled_tasks.is_empty());
+}
+
+#[test]
+fn proverd_task_priority() {
+    let task = |block: u64, mock: bool, priority: Option<i32>, edition: u64| ProofRequest {
+        options: ProofRequestOptions {
+            circuit: "super".to_string(),
+            block,
+            rpc: "http://localhost:1111".to_string(),
+            mock,
+            priority,
+            ..Default::default()
+        },
+        result: None,
+        edition,
+        lease: None,
+    };
+    let expected = vec![
+        task(9, true, Some(1), 0),
+        task(2, false, None, 0),
+        task(3, false, Some(0), 0),
+        task(1, false, None, 1),
+        task(1, true, None, 0),
+        task(1, false, Some(-1), 0),
+    ];
+
+    let mut tasks = expected.clone();
+    tasks.reverse();
+    tasks.sort_by(cmp_task_priority);
+    let keys = |tasks: &[ProofRequest]| -> Vec<(u64, bool, Option<i32>, u64)> {
+        tasks
+            .iter()
+            .map(|e| {
+                (
+                    e.options.block,
+                    e.options.mock,
+                    e.options.priority,
+                    e.edition,
+                )
+            })
+            .collect()
+    };
+    assert_eq!(keys(&tasks), keys(&expected));
+}
+
+#[tokio::test]
+async fn proverd_claim_race() {
+    init_logger();
+
+    let node_a = SharedState::new("a".to_string(), Some("127.0.0.1:11113".to_string()));
+    let node_b = SharedState::new("b".to_string(), Some("127.0.0.1:11114".to_string()));
+    {
+        let _ = serve(&node_a, node_b.ro.node_lookup.as_ref().unwrap());
+        let _ = serve(&node_b, node_a.ro.node_lookup.as_ref().unwrap());
+    }
+    sleep(Duration::from_millis(300)).await;
+
+    for block in 1..10 {
+        let proof = ProofRequestOptions {
+            circuit: "super".to_string(),
+            block,
+            retry: false,
+            rpc: "http://localhost:1111".to_string(),
+            ..Default::default()
+        };
+        assert!(node_a.get_or_enqueue(&proof).await.is_none());
+        assert!(node_b.get_or_enqueue(&proof).await.is_none());
+
+        // both nodes try to claim the same task at the same time
+        let (mut skip_a, mut skip_b) = (Vec::new(), Vec::new());
+        let (res_a, res_b) = tokio::join!(
+            node_a.obtain_slot(&mut skip_a),
+            node_b.obtain_slot(&mut skip_b)
+        );
+        assert!(res_a.is_some() ^ res_b.is_some(), "block={}", block);
+
+        // the loser can't take the task over while the winner holds it
+        let _ = node_a.merge_tasks_from_peers().await;
+        let _ = node_b.merge_tasks_from_peers().await;
+        assert!(node_a.obtain_slot(&mut Vec::new()).await.is_none());
+        assert!(node_b.obtain_slot(&mut Vec::new()).await.is_none());
+
+        // clears the slots, the cancellation is forwarded to the other node
+        assert!(node_a.cancel(&proof).await);
+        assert!(!node_b.cancel(&proof).await);
+        assert!(node_b.rw.lock().await.tasks.is_empty());
+
+        // not merged again
+        let _ = node_a.merge_tasks_from_peers().await;
+        assert!(node_a.rw.lock().await.tasks.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn proverd_lease_expiry() {
+    init_logger();
+
+    let node = SharedState::new("a".to_string(), None);
+    let proof = ProofRequestOptions {
+        circuit: "super".to_string(),
+        block: 1,
+        retry: false,
+        rpc: "http://localhost:1111".to_string(),
+        ..Default::default()
+    };
+
+    // a task claimed by a node that disappeared mid-proof
+    node.rw.lock().await.tasks.push(ProofRequest {
+        options: proof.clone(),
+        result: None,
+        edition: 0,
+        lease: Some(TaskLease {
+            node_id: "b".to_string(),
+            expires: unix_time_ms() + 500,
+        }),
+    });
+    assert!(node.obtain_slot(&mut Vec::new()).await.is_none());
+
+    // recovered once the lease expired
+    sleep(Duration::from_millis(600)).await;
+    node.duty_cycle().await;
+    assert!(node.get_or_enqueue(&proof).await.is_some());
+}
+
+#[tokio::test]
+async fn proverd_static_discovery() {
+    init_logger();
+
+    let node_a = SharedState::new("a".to_string(), None);
+    // the first peer is unreachable
+    let node_b = SharedState::new("b".to_string(), None).with_discovery(Arc::new(
+        StaticDiscovery::from_list("127.0.0.1:11116, 127.0.0.1:11115"),
+    ));
+    let _ = serve(&node_a, "127.0.0.1:11115");
+    sleep(Duration::from_millis(300)).await;
+
+    let proof = ProofRequestOptions {
+        circuit: "super".to_string(),
+        block: 1,
+        retry: false,
+        rpc: "http://localhost:1111".to_string(),
+        ..Default::default()
+    };
+    assert!(node_a.get_or_enqueue(&proof).await.is_none());
+    node_a.duty_cycle().await;
+
+    assert!(node_b.merge_tasks_from_peers().await.is_ok());
+    assert!(node_b.get_or_enqueue(&proof).await.is_some());
+}
+
+#[tokio::test]
+async fn proverd_unreachable_peer() {
+    init_logger();
+
+    // a port that nobody listens on
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .expect("bind")
+        .local_addr()
+        .unwrap();
+    let mut node = SharedState::new("a".to_string(), None)
+        .with_discovery(Arc::new(StaticDiscovery::from_list(&addr.to_string())));
+    node.ro.lease_duration = Duration::from_millis(300);
+    let proof = ProofRequestOptions {
+        circuit: "super".to_string(),
+        block: 1,
+        retry: false,
+        rpc: "http://localhost:1111".to_string(),
+        ..Default::default()
+    };
+    node.rw.lock().await.tasks.push(ProofRequest {
+        options: proof.clone(),
+        result: None,
+        edition: 0,
+        lease: None,
+    });
+
+    // the peer may hold a lease on the task
+    assert!(node.obtain_slot(&mut Vec::new()).await.is_none());
+
+    // not anymore once it was unreachable for longer than a lease
+    sleep(Duration::from_millis(400)).await;
+    assert!(node.obtain_slot(&mut Vec::new()).await.is_some());
+}
+
+#[tokio::test]
+async fn proverd_auth() {
+    init_logger();
+
+    let mut node = SharedState::new("a".to_string(), None);
+    node.ro.auth = AuthConfig {
+        token: Some("full".to_string()),
+        read_token: Some("read".to_string()),
+    };
+    let _ = serve(&node, "127.0.0.1:11117");
+    sleep(Duration::from_millis(300)).await;
+
+    let client = hyper::Client::new();
+    let uri = hyper::Uri::from_static("http://127.0.0.1:11117");
+    let call = |token: Option<&'static str>, method: &'static str| {
+        jsonrpc_call_with_token::<_, serde_json::Value>(
+            5000,
+            &client,
+            &uri,
+            token,
+            method,
+            serde_json::json!([]),
+        )
+    };
+
+    assert!(call(None, "status").await.is_err());
+    assert!(call(Some("wrong"), "status").await.is_err());
+    assert!(call(Some("read"), "status").await.is_ok());
+    assert!(call(Some("read"), "flushAll").await.is_err());
+    assert!(call(Some("full"), "flushAll").await.is_ok());
+}
+
+#[tokio::test]
+async fn proverd_auth_peers() {
+    init_logger();
+
+    // characters that are not allowed in the userinfo of an uri
+    let auth = AuthConfig {
+        token: Some("a@b/c:d%".to_string()),
+        read_token: None,
+    };
+    let mut node_a = SharedState::new("a".to_string(), None);
+    node_a.ro.auth = auth.clone();
+    let mut node_b = SharedState::new("b".to_string(), None)
+        .with_discovery(Arc::new(StaticDiscovery::from_list("127.0.0.1:11119")));
+    let _ = serve(&node_a, "127.0.0.1:11119");
+    sleep(Duration::from_millis(300)).await;
+
+    // without the token
+    assert!(node_b.merge_tasks_from_peers().await.is_ok());
+    assert!(node_b.ro.metrics.merge_failures.get() > 0.0);
+
+    node_b.ro.auth = auth;
+    let failures = node_b.ro.metrics.merge_failures.get();
+    assert!(node_b.merge_tasks_from_peers().await.is_ok());
+    assert_eq!(node_b.ro.metrics.merge_failures.get(), failures);
+}
+
+#[tokio::test]
+async fn proverd_metrics() {
+    init_logger();
+
+    let node = SharedState::new("a".to_string(), None);
+    let _ = serve(&node, "127.0.0.1:11118");
+    sleep(Duration::from_millis(300)).await;
+
+    let proof = ProofRequestOptions {
+        circuit: "super".to_string(),
+        block: 1,
+        retry: false,
+        rpc: "http://localhost:1111".to_string(),
+        ..Default::default()
+    };
+    assert!(node.get_or_enqueue(&proof).await.is_none());
+    node.duty_cycle().await;
+
+    let resp = hyper::Client::new()
+        .get("http://127.0.0.1:11118/metrics".parse().unwrap())
+        .await
+        .expect("response");
+    let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(body.contains("proverd_tasks{state=\"failed\"} 1\n"));
+    assert!(body.contains("proverd_tasks{state=\"queued\"} 0\n"));
+    assert!(body.contains("proverd_task_failures_total{kind=\"other\"} 1\n"));
+}
+
+#[tokio::test]
+async fn proverd_multi_block() {
+    init_logger();
+
+    let node = SharedState::new("a".to_string(), None);
+    let proof = |to_block: Option<u64>, aggregate: bool| ProofRequestOptions {
+        circuit: "super".to_string(),
+        block: 2,
+        to_block,
+        retry: false,
+        rpc: "http://localhost:1111".to_string(),
+        aggregate,
+        ..Default::default()
+    };
+
+    // the block range is part of the task identity
+    assert!(proof(None, true) != proof(Some(3), true));
+    assert!(proof(Some(2), true) != proof(Some(3), true));
+
+    // invalid ranges fail before fetching any witness
+    let err = node
+        .compute_proof(&proof(Some(1), true))
+        .await
+        .expect_err("to_block before block");
+    assert!(err.contains("before block"));
+    let err = node
+        .compute_proof(&proof(Some(3), false))
+        .await
+        .expect_err("not aggregated");
+    assert!(err.contains("require aggregate"));
+}
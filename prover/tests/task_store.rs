@@ -0,0 +1,65 @@
+use prover::task_store::FileTaskStore;
+use prover::task_store::TaskStore;
+use zkevm_common::prover::*;
+
+fn task(block: u64) -> ProofRequest {
+    ProofRequest {
+        options: ProofRequestOptions {
+            circuit: "super".to_string(),
+            block,
+            rpc: "http://localhost:1111".to_string(),
+            ..Default::default()
+        },
+        result: None,
+        edition: 0,
+        lease: None,
+    }
+}
+
+#[test]
+fn file_task_store() {
+    let data_dir =
+        std::env::temp_dir().join(format!("proverd-file-task-store-{}", rand::random::<u64>()));
+    let data_dir = data_dir.to_str().unwrap();
+    let task_store = FileTaskStore::new(data_dir).expect("task store");
+    assert!(task_store.load().expect("load").is_empty());
+
+    task_store.save(&task(1)).expect("save");
+    task_store.save(&task(2)).expect("save");
+    // replaces the task with the same options
+    let mut completed = task(1);
+    completed.result = Some(Err("failed".to_string()));
+    completed.edition = 1;
+    task_store.save(&completed).expect("save");
+    task_store.remove(&task(2).options).expect("remove");
+    task_store.remove(&task(3).options).expect("remove");
+
+    let tasks = task_store.load().expect("load");
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].options, completed.options);
+    assert_eq!(tasks[0].edition, 1);
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn file_task_store_migration() {
+    let data_dir = std::env::temp_dir().join(format!(
+        "proverd-file-task-store-migration-{}",
+        rand::random::<u64>()
+    ));
+    std::fs::create_dir_all(&data_dir).expect("create dir");
+    // the single file of previous versions
+    let legacy = data_dir.join("tasks.json");
+    std::fs::write(&legacy, serde_json::to_vec(&[task(1), task(2)]).unwrap()).expect("write");
+
+    let task_store = FileTaskStore::new(data_dir.to_str().unwrap()).expect("task store");
+    let mut tasks = task_store.load().expect("load");
+    tasks.sort_by_key(|e| e.options.block);
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[1].options, task(2).options);
+    assert!(!legacy.exists());
+    assert_eq!(task_store.load().expect("load").len(), 2);
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
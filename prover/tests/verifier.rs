@@ -188,23 +188,35 @@ macro_rules! gen_match {
 
                 let agg_params =
                     ProverParams::setup(CIRCUIT_CONFIG.min_k_aggregation as u32, fixed_rng());
-                let agg_circuit = AggregationCircuit::new(&agg_params, [snark], fixed_rng());
-                let agg_vk = keygen_vk(&agg_params, &agg_circuit).expect("vk");
-
-                let mut data = Verifier::default();
-                data.label = format!("{}-{}-a", $LABEL, CIRCUIT_CONFIG.block_gas_limit);
-                data.config = CIRCUIT_CONFIG;
-                data.runtime_code = gen_verifier(
-                    &agg_params,
-                    &agg_vk,
-                    Config::kzg()
-                        .with_num_instance(AggregationCircuit::num_instance())
-                        .with_accumulator_indices(Some(AggregationCircuit::accumulator_indices())),
-                )
-                .into();
-
-                let data = data.build();
-                write_bytes(&data.label, &serde_json::to_vec(data).unwrap());
+                // one verifier per number of aggregated blocks,
+                // the suffix matches the label of `SharedState::aggregate_proofs`.
+                for n in 1..=MAX_AGGREGATION_BLOCKS {
+                    let suffix = match n {
+                        1 => String::new(),
+                        n => n.to_string(),
+                    };
+                    let agg_circuit =
+                        AggregationCircuit::new(&agg_params, vec![snark.clone(); n], fixed_rng());
+                    let agg_vk = keygen_vk(&agg_params, &agg_circuit).expect("vk");
+
+                    let mut data = Verifier::default();
+                    data.label =
+                        format!("{}-{}-a{}", $LABEL, CIRCUIT_CONFIG.block_gas_limit, suffix);
+                    data.config = CIRCUIT_CONFIG;
+                    data.runtime_code = gen_verifier(
+                        &agg_params,
+                        &agg_vk,
+                        Config::kzg()
+                            .with_num_instance(agg_circuit.num_instance())
+                            .with_accumulator_indices(Some(
+                                AggregationCircuit::accumulator_indices(),
+                            )),
+                    )
+                    .into();
+
+                    let data = data.build();
+                    write_bytes(&data.label, &serde_json::to_vec(data).unwrap());
+                }
             },
             {
                 panic!("no circuit parameters found");
@@ -42,6 +42,12 @@ COORDINATOR_DUMMY_PROVER=true cargo test -p coordinator -- finalize_chain --igno
 # now run all default tests
 COORDINATOR_DUMMY_PROVER=false cargo test -p coordinator -- $@
 status=$?
+
+# finalize multiple blocks with one aggregated proof
+if [ $status -eq 0 ] && [ "$COORDINATOR_AGGREGATE_PROOF" = "true" ]; then
+  COORDINATOR_DUMMY_PROVER=false cargo test -p coordinator -- finalize_blocks_batch --ignored
+  status=$?
+fi
 FAILED_BLOCKS=$(./scripts/rpc_prover.sh info | jq -cr '.result.tasks | map(select(.result.Err)) | map(.options.block) | .[]')
 
 pkill -9 prover_rpcd || true